@@ -2,10 +2,7 @@ use chess::Action;
 use libopenai::Client;
 use rustygen::{
     agent::Agent,
-    assistants::{
-        chess::ChessEngine,
-        gpt::{ChessError, ChessGPT},
-    },
+    assistants::{chess::ChessEngine, gpt::ChessGPT},
     Conversation, MainConversation,
 };
 use std::time::Duration;
@@ -16,29 +13,17 @@ async fn main() -> color_eyre::Result<()> {
     dotenv::dotenv()?;
     let client = Client::new(None, None)?;
 
-    let mut i = 0;
     let mut conversation = MainConversation::<chess::Game>::new()
-        .while_loop(|game| {
+        .while_loop(|game, i| {
             println!("Round {i}");
-            i += 1;
             game.result().is_none()
         })
         .agent(ChessEngine::new("./stockfish-ubuntu-x86-64", Duration::from_secs(1)).await?)
         .agent(
-            ChessGPT::new("gpt-3.5-turbo", client, 5).catch(|e| async move {
-                match e {
-                    // Fall back to Stockfish whenever ChatGPT fails generating a legal move
-                    ChessError::NoLegalMoveFound => {
-                        println!("Resorting to Stockfish for GPT's move");
-                        Ok(
-                            ChessEngine::new("./stockfish-ubuntu-x86-64", Duration::from_secs(1))
-                                .await
-                                .unwrap(),
-                        )
-                    }
-                    e => Err(e),
-                }
-            }),
+            // Fall back to Stockfish whenever ChatGPT fails generating a legal move
+            ChessGPT::new("gpt-3.5-turbo", client, 5).or_else(
+                ChessEngine::new("./stockfish-ubuntu-x86-64", Duration::from_secs(1)).await?,
+            ),
         )
         .end_while();
 
@@ -0,0 +1,50 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Expr, ExprLit, Lit, Meta};
+
+/// Lines of the doc comments of an item
+pub fn lines(attrs: &[Attribute]) -> Vec<String> {
+    return attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => Some(lit.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+}
+
+/// Joins the specified lines into a description, if they aren't empty
+pub fn join<S: AsRef<str>>(lines: &[S]) -> Option<String> {
+    let description = lines
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let description = description.trim();
+    return (!description.is_empty()).then(|| description.to_string());
+}
+
+/// Description of an item, taken from it's doc comments
+#[inline]
+pub fn description(attrs: &[Attribute]) -> Option<String> {
+    return join(&lines(attrs));
+}
+
+/// Expression of type `Option<&'static str>`
+pub fn option(description: Option<&str>) -> TokenStream {
+    return match description {
+        Some(description) => quote! { ::core::option::Option::Some(#description) },
+        None => quote! { ::core::option::Option::None },
+    };
+}
@@ -0,0 +1,42 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+mod docs;
+mod schema;
+mod tool;
+
+/// Implements `ToolSchema` for a struct or enum.
+///
+/// - Structs with named fields become objects, whose fields are required unless they're optional (like `Option`)
+///   or marked with `#[serde(default)]`.
+/// - Newtype structs have the schema of the type they wrap.
+/// - Enums whose variants are all units become strings, restricted to the names of the variants.
+///
+/// Doc comments of the type and it's fields are used as their descriptions. Of serde's attributes,
+/// only `rename`, `default` and `deny_unknown_fields` are supported, since the rest of them could
+/// make the schema differ from what's deserialized.
+#[proc_macro_derive(ToolSchema, attributes(serde))]
+pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    return schema::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into();
+}
+
+/// Generates a function, named after the annotated one with a `_tool` suffix, that builds a `Tool` calling it.
+///
+/// The annotated function must be `async`, and every one of it's arguments must implement both `ToolSchema`
+/// and `Deserialize`. It's doc comment is used as the tool's description, with the exception of the
+/// `# Arguments` section, whose entries (``* `name` - description``) describe each argument.
+///
+/// Functions whose return type is named `Result` (like `color_eyre::Result<T>`) fail the call with their error,
+/// whilst the value returned by any other function is the result of the call.
+///
+/// The name of the tool can be overriden with `#[tool(name = "...")]`.
+#[proc_macro_attribute]
+pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+    return tool::expand(attr.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into();
+}
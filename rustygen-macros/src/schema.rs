@@ -0,0 +1,144 @@
+use crate::docs;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_quote, Attribute, Data, DeriveInput, Fields, GenericParam, LitStr, Token,
+};
+
+/// Serde attributes of a container, field or variant, which affect it's schema
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    default: bool,
+}
+
+impl SerdeAttrs {
+    /// Parses the serde attributes of an item, failing on the ones the schema can't account for
+    fn parse(attrs: &[Attribute], allowed: &[&str]) -> syn::Result<Self> {
+        let mut result = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if !allowed.iter().any(|allowed| meta.path.is_ident(allowed)) {
+                    return Err(meta.error(
+                        "unsupported serde attribute, since it could make the schema differ from what's deserialized",
+                    ));
+                }
+
+                if meta.path.is_ident("rename") {
+                    result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<LitStr>()?;
+                    }
+                    result.default = true;
+                }
+                return Ok(());
+            })?;
+        }
+
+        return Ok(result);
+    }
+}
+
+pub fn derive(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let container = SerdeAttrs::parse(&input.attrs, &["rename", "default", "deny_unknown_fields"])?;
+    let description = docs::description(&input.attrs);
+    let description = docs::option(description.as_deref());
+
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let properties = fields
+                    .named
+                    .iter()
+                    .map(|field| {
+                        let attrs = SerdeAttrs::parse(&field.attrs, &["rename", "default"])?;
+                        let ident = field.ident.as_ref().unwrap();
+                        let name = attrs.rename.unwrap_or_else(|| ident.unraw().to_string());
+                        let ty = &field.ty;
+                        let field_description = docs::description(&field.attrs);
+                        let field_description = docs::option(field_description.as_deref());
+                        let default = container.default || attrs.default;
+
+                        Ok(quote! {
+                            (
+                                #name,
+                                <#ty as ::rustygen::tool::ToolSchema>::schema(),
+                                #field_description,
+                                !#default && !<#ty as ::rustygen::tool::ToolSchema>::OPTIONAL,
+                            )
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+
+                quote! {
+                    ::rustygen::tool::__private::object(#description, ::std::vec![#(#properties),*])
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                quote! {
+                    ::rustygen::tool::__private::describe(
+                        <#ty as ::rustygen::tool::ToolSchema>::schema(),
+                        #description,
+                    )
+                }
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`ToolSchema` can only be derived for structs with named fields, or newtype structs",
+                ))
+            }
+        },
+        Data::Enum(data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    if !matches!(variant.fields, Fields::Unit) {
+                        return Err(syn::Error::new_spanned(
+                            &variant.fields,
+                            "`ToolSchema` can only be derived for enums whose variants are all units",
+                        ));
+                    }
+
+                    let attrs = SerdeAttrs::parse(&variant.attrs, &["rename"])?;
+                    Ok(attrs
+                        .rename
+                        .unwrap_or_else(|| variant.ident.unraw().to_string()))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! {
+                ::rustygen::tool::__private::enumeration(#description, &[#(#variants),*])
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "`ToolSchema` can't be derived for unions",
+            ))
+        }
+    };
+
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(parse_quote!(::rustygen::tool::ToolSchema));
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    return Ok(quote! {
+        impl #impl_generics ::rustygen::tool::ToolSchema for #ident #ty_generics #where_clause {
+            fn schema() -> ::rustygen::tool::__private::serde_json::Value {
+                #body
+            }
+        }
+    });
+}
@@ -0,0 +1,222 @@
+use crate::docs;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, parse::Parser, FnArg, Ident, ItemFn, LitStr, Pat, ReturnType, Type};
+
+/// Documentation of a tool, split into it's description and the ones of it's arguments
+struct ToolDocs {
+    description: Option<String>,
+    arguments: Vec<(String, String)>,
+}
+
+impl ToolDocs {
+    /// Splits the doc comments of a function on it's `# Arguments` section
+    fn parse(lines: Vec<String>) -> Self {
+        let section = lines.iter().position(|line| {
+            let heading = line.trim_start_matches('#').trim();
+            line.starts_with('#')
+                && (heading.eq_ignore_ascii_case("arguments")
+                    || heading.eq_ignore_ascii_case("parameters"))
+        });
+
+        let Some(section) = section else {
+            return Self {
+                description: docs::join(&lines),
+                arguments: Vec::new(),
+            };
+        };
+
+        let end = lines[section + 1..]
+            .iter()
+            .position(|line| line.starts_with('#'))
+            .map_or(lines.len(), |i| section + 1 + i);
+
+        let mut arguments = Vec::<(String, String)>::new();
+        for line in &lines[section + 1..end] {
+            let trimmed = line.trim();
+            let Some(entry) = trimmed
+                .strip_prefix('*')
+                .or_else(|| trimmed.strip_prefix('-'))
+            else {
+                // Continuation of the previous argument's description
+                if let Some((_, description)) = arguments.last_mut().filter(|_| !trimmed.is_empty())
+                {
+                    description.push(' ');
+                    description.push_str(trimmed);
+                }
+                continue;
+            };
+
+            let entry = entry.trim();
+            let (name, description) = match entry.strip_prefix('`') {
+                Some(entry) => entry.split_once('`').unwrap_or((entry, "")),
+                None => entry
+                    .split_once(':')
+                    .or_else(|| entry.split_once(" - "))
+                    .unwrap_or((entry, "")),
+            };
+
+            let description = description.trim().trim_start_matches([':', '-']).trim();
+            let name = name.trim();
+            let name = name.strip_prefix("r#").unwrap_or(name);
+            arguments.push((name.to_string(), description.to_string()));
+        }
+
+        let remaining = lines[..section]
+            .iter()
+            .chain(&lines[end..])
+            .collect::<Vec<_>>();
+
+        return Self {
+            description: docs::join(&remaining),
+            arguments,
+        };
+    }
+}
+
+pub fn expand(attr: TokenStream, item: ItemFn) -> syn::Result<TokenStream> {
+    let mut name = item.sig.ident.unraw().to_string();
+    if !attr.is_empty() {
+        syn::meta::parser(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                return Ok(());
+            }
+            return Err(meta.error("expected `name`"));
+        })
+        .parse2(attr)?;
+    }
+
+    if item.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            item.sig.fn_token,
+            "tools must be `async` functions",
+        ));
+    }
+
+    if !item.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.sig.generics,
+            "tools can't be generic",
+        ));
+    }
+
+    let mut idents = Vec::<&Ident>::new();
+    let mut types = Vec::<&Type>::new();
+    for input in item.sig.inputs.iter() {
+        let FnArg::Typed(input) = input else {
+            return Err(syn::Error::new_spanned(input, "tools can't take `self`"));
+        };
+
+        let Pat::Ident(pat) = &*input.pat else {
+            return Err(syn::Error::new_spanned(
+                &input.pat,
+                "tool arguments must be bound to an identifier",
+            ));
+        };
+
+        if matches!(&*input.ty, Type::Reference(_) | Type::ImplTrait(_)) {
+            return Err(syn::Error::new_spanned(
+                &input.ty,
+                "tool arguments must be owned, concrete types",
+            ));
+        }
+
+        idents.push(&pat.ident);
+        types.push(&input.ty);
+    }
+
+    let docs = ToolDocs::parse(docs::lines(&item.attrs));
+    if let Some((argument, _)) = docs
+        .arguments
+        .iter()
+        .find(|(argument, _)| !idents.iter().any(|ident| ident.unraw() == argument))
+    {
+        return Err(syn::Error::new(
+            item.sig.ident.span(),
+            format!("documented argument `{argument}` isn't an argument of the tool"),
+        ));
+    }
+
+    let names = idents
+        .iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect::<Vec<_>>();
+
+    let descriptions = names.iter().map(|name| {
+        let description = docs
+            .arguments
+            .iter()
+            .find(|(argument, _)| argument == name)
+            .map(|(_, description)| description.as_str())
+            .filter(|description| !description.is_empty());
+        docs::option(description)
+    });
+
+    let description = match docs.description.as_deref() {
+        Some(description) => {
+            quote! { ::core::option::Option::Some(::std::borrow::Cow::Borrowed(#description)) }
+        }
+        None => quote! { ::core::option::Option::None },
+    };
+
+    let function = &item.sig.ident;
+    let call = quote! { #function(#(#idents),*).await };
+    let call = match &item.sig.output {
+        ReturnType::Type(_, ty) if returns_result(ty) => call,
+        _ => quote! { ::core::result::Result::<_, ::core::convert::Infallible>::Ok(#call) },
+    };
+
+    let vis = &item.vis;
+    let builder = format_ident!("{}_tool", function.unraw(), span = function.span());
+    let builder_doc = format!("Builds the tool that calls [`{}`]", function.unraw());
+
+    return Ok(quote! {
+        #item
+
+        #[doc = #builder_doc]
+        #vis fn #builder() -> ::rustygen::tool::Tool {
+            #[derive(::rustygen::tool::__private::serde::Deserialize)]
+            #[serde(crate = "::rustygen::tool::__private::serde")]
+            struct __ToolArguments {
+                #(#idents: #types,)*
+            }
+
+            let parameters = ::rustygen::tool::__private::object(
+                ::core::option::Option::None,
+                ::std::vec![#(
+                    (
+                        #names,
+                        <#types as ::rustygen::tool::ToolSchema>::schema(),
+                        #descriptions,
+                        !<#types as ::rustygen::tool::ToolSchema>::OPTIONAL,
+                    )
+                ),*],
+            );
+
+            return ::rustygen::tool::Tool::new(
+                #name,
+                #description,
+                parameters,
+                |__ToolArguments { #(#idents),* }: __ToolArguments| async move { #call },
+            );
+        }
+    });
+}
+
+/// Whether the function returns a `Result`, judging by the name of it's return type.
+///
+/// Only types named exactly `Result` count (like `std::io::Result<T>` or `color_eyre::Result<T>`),
+/// so that types like `SearchResult` are returned as they are.
+fn returns_result(ty: &Type) -> bool {
+    return match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Result"),
+        Type::Paren(paren) => returns_result(&paren.elem),
+        Type::Group(group) => returns_result(&group.elem),
+        _ => false,
+    };
+}
@@ -1,13 +1,54 @@
-use crate::{control_flow::error::Catch, record::Record};
+use crate::{
+    control_flow::{
+        error::{Catch, CatchWithRecord, Finally, OrElse, RecoverWithRecord},
+        race::Race,
+        retry::{Retry, RetryPolicy},
+        timeout::Timeout,
+        transaction::Transactional,
+    },
+    record::Record,
+    Str,
+};
 use libopenai::chat::Role;
-use std::{future::Future, marker::PhantomData, pin::Pin, rc::Rc, sync::Arc};
+use std::{future::Future, marker::PhantomData, pin::Pin, rc::Rc, sync::Arc, time::Duration};
 
 pub trait Agent<R: Record> {
     type Error: Into<color_eyre::Report>;
 
+    /// Kind of agent, used to identify it in the path of a [`ConversationError`](crate::error::ConversationError)
+    const KIND: &'static str = "agent";
+
     #[allow(async_fn_in_trait)]
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error>;
 
+    /// Name of the agent, if it has one
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        return None;
+    }
+
+    /// Description of the agent's role, if it has one
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        return None;
+    }
+
+    /// Gives this agent a name.
+    ///
+    /// The name identifies the agent in errors and group chats, but it's only attached to the messages the agent
+    /// pushes to the record when it takes part in a group chat. Agents that always attach it
+    /// (like [`ChatGPT`](crate::assistants::gpt::ChatGPT)) have their own way to be named.
+    fn named(self, name: impl Into<Str>) -> Named<Self>
+    where
+        Self: Sized,
+    {
+        return Named {
+            agent: self,
+            name: name.into(),
+            description: None,
+        };
+    }
+
     /// Catches the error returned by this agent, and handles it
     fn catch<
         F: FnMut(Self::Error) -> Fut,
@@ -22,6 +63,115 @@ pub trait Agent<R: Record> {
     {
         return Catch { agent: self, f };
     }
+
+    /// Catches the error returned by this agent, and handles it with access to the record.
+    ///
+    /// The handler can modify the record before returning it's future, and return a replacement agent to be played.
+    /// Handlers that only resolve the error in place should use [`recover_with_record`](Agent::recover_with_record).
+    fn catch_with_record<
+        F: FnMut(&mut R, Self::Error) -> Fut,
+        Fut: Future<Output = Result<Option<A>, Self::Error>>,
+        A: Agent<R>,
+    >(
+        self,
+        f: F,
+    ) -> CatchWithRecord<Self, F>
+    where
+        Self: Sized,
+    {
+        return CatchWithRecord { agent: self, f };
+    }
+
+    /// Catches the error returned by this agent, and resolves it in place with access to the record.
+    ///
+    /// The handler can modify the record before returning it's future, which may fail with another error.
+    fn recover_with_record<
+        F: FnMut(&mut R, Self::Error) -> Fut,
+        Fut: Future<Output = Result<(), Self::Error>>,
+    >(
+        self,
+        f: F,
+    ) -> RecoverWithRecord<Self, F>
+    where
+        Self: Sized,
+    {
+        return RecoverWithRecord { agent: self, f };
+    }
+
+    /// Plays `fallback` whenever this agent fails
+    fn or_else<B: Agent<R>>(self, fallback: B) -> OrElse<Self, B>
+    where
+        Self: Sized,
+    {
+        return OrElse {
+            agent: self,
+            fallback,
+        };
+    }
+
+    /// Always plays `cleanup` after this agent, whether it succeeded or not.
+    ///
+    /// If this agent fails, it's error is kept and returned after the cleanup.
+    fn finally<C: Agent<R>>(self, cleanup: C) -> Finally<Self, C>
+    where
+        Self: Sized,
+    {
+        return Finally {
+            agent: self,
+            cleanup,
+        };
+    }
+
+    /// Re-runs this agent whenever it fails, following the specified policy
+    fn retry(self, policy: RetryPolicy) -> Retry<Self, fn(&Self::Error) -> bool>
+    where
+        Self: Sized,
+    {
+        return self.retry_if::<fn(&Self::Error) -> bool>(policy, |_| true);
+    }
+
+    /// Re-runs this agent whenever it fails with an error for which `predicate` holds, following the specified policy
+    fn retry_if<F: FnMut(&Self::Error) -> bool>(
+        self,
+        policy: RetryPolicy,
+        predicate: F,
+    ) -> Retry<Self, F>
+    where
+        Self: Sized,
+    {
+        return Retry {
+            agent: self,
+            policy,
+            predicate,
+        };
+    }
+
+    /// Cancels this agent, failing with [`TimeoutError::Elapsed`](crate::control_flow::timeout::TimeoutError::Elapsed), if it doesn't finish within `duration`
+    fn timeout(self, duration: Duration) -> Timeout<Self>
+    where
+        Self: Sized,
+    {
+        return Timeout {
+            agent: self,
+            duration,
+        };
+    }
+
+    /// Restores the record to it's previous state whenever this agent fails
+    fn transactional(self) -> Transactional<Self>
+    where
+        Self: Sized,
+    {
+        return Transactional { agent: self };
+    }
+
+    /// Races this agent against `other`, keeping the record of the first one to succeed
+    fn race_with<'a, A: 'a + Agent<R>>(self, other: A) -> Race<'a, R>
+    where
+        Self: 'a + Sized,
+    {
+        return Race::new().race_with(self).race_with(other);
+    }
 }
 
 /// An agent that can be executed through shared reference
@@ -64,42 +214,97 @@ impl<R: Record, E: Clone + Into<color_eyre::Report>> AgentRef<R> for Result<(),
 
 impl<R: Record, A: Agent<R>> Agent<R> for Box<A> {
     type Error = A::Error;
+    const KIND: &'static str = A::KIND;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         A::handle(self, record).await
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        A::name(self)
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        A::description(self)
+    }
 }
 
 impl<R: Record, A: AgentRef<R>> Agent<R> for Rc<A> {
     type Error = A::Error;
+    const KIND: &'static str = A::KIND;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         A::handle_ref(self, record).await
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        A::name(self)
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        A::description(self)
+    }
 }
 
 impl<R: Record, A: AgentRef<R>> Agent<R> for Arc<A> {
     type Error = A::Error;
+    const KIND: &'static str = A::KIND;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         A::handle_ref(self, record).await
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        A::name(self)
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        A::description(self)
+    }
 }
 
 impl<R: Record, A: Agent<R>> Agent<R> for &mut A {
     type Error = A::Error;
+    const KIND: &'static str = A::KIND;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         A::handle(self, record).await
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        A::name(self)
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        A::description(self)
+    }
 }
 
 impl<R: Record, A: AgentRef<R>> Agent<R> for &A {
     type Error = A::Error;
+    const KIND: &'static str = A::KIND;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         A::handle_ref(self, record).await
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        A::name(self)
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        A::description(self)
+    }
 }
 
 impl<R: Record, A: AgentRef<R>> AgentRef<R> for Rc<A> {
@@ -137,6 +342,48 @@ where
     }
 }
 
+/* NAMED AGENT */
+
+/// Agent with a name, used to identify it in errors
+pub struct Named<A> {
+    pub(crate) agent: A,
+    pub(crate) name: Str,
+    pub(crate) description: Option<Str>,
+}
+
+impl<A> Named<A> {
+    /// Describes the role of the agent
+    pub fn with_description(mut self, description: impl Into<Str>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl<R: Record, A: Agent<R>> Agent<R> for Named<A> {
+    type Error = A::Error;
+    const KIND: &'static str = A::KIND;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        self.agent.handle(record).await
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        return Some(&self.name);
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        return self.description.as_deref();
+    }
+}
+
+impl<R: Record, A: AgentRef<R>> AgentRef<R> for Named<A> {
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        self.agent.handle_ref(record).await
+    }
+}
+
 /* DYNAMIC AGENT */
 
 pub(crate) struct DynAgent<'a, R: 'static> {
@@ -145,13 +392,23 @@ pub(crate) struct DynAgent<'a, R: 'static> {
     _phtm: PhantomData<&'a mut &'a ()>,
 }
 
-impl<'a, R: Record> DynAgent<'a, R> {
+impl<'a, R> DynAgent<'a, R> {
     pub fn handle<'b>(
         &'b mut self,
         record: &'b mut R,
     ) -> Pin<Box<dyn 'b + Future<Output = color_eyre::Result<()>>>> {
         return (self.vtable.handle)(self.data, record);
     }
+
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        return self.vtable.kind;
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<Str> {
+        return (self.vtable.name)(self.data);
+    }
 }
 
 impl<'a, R: Record> DynAgent<'a, R> {
@@ -180,6 +437,8 @@ pub(crate) struct DynAgentVTable<R> {
         *mut (),
         &'a mut R,
     ) -> Pin<Box<dyn 'a + Future<Output = color_eyre::Result<()>>>>,
+    kind: &'static str,
+    name: fn(*mut ()) -> Option<Str>,
     drop: fn(*mut ()),
 }
 
@@ -199,8 +458,15 @@ impl<R: Record> DynAgentVTable<R> {
             });
         }
 
+        fn name_agent<R: Record, A: Agent<R>>(ptr: *mut ()) -> Option<Str> {
+            let this = unsafe { &*ptr.cast::<A>() };
+            return this.name().map(|name| Str::Owned(name.to_string()));
+        }
+
         return Self {
             handle: handle_agent::<R, A>,
+            kind: A::KIND,
+            name: name_agent::<R, A>,
             drop: drop_agent::<R, A>,
         };
     }
@@ -1,4 +1,7 @@
-use crate::{agent::Agent, record::Record};
+use crate::{
+    agent::Agent,
+    record::{Record, Snapshot},
+};
 use chess::ChessMove;
 use chessgineer::{game::Game, Context};
 use failure::{Compat, Fail};
@@ -47,6 +50,20 @@ impl Record for chess::Game {
     }
 }
 
+impl Snapshot for chess::Game {
+    type State = chess::Game;
+
+    #[inline]
+    fn snapshot(&self) -> Self::State {
+        return self.clone();
+    }
+
+    #[inline]
+    fn restore(&mut self, state: Self::State) {
+        *self = state;
+    }
+}
+
 impl Agent<chess::Game> for ChessEngine {
     type Error = std::io::Error;
 
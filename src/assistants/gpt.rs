@@ -1,114 +1,305 @@
 use crate::{
     agent::{Agent, AgentRef},
+    group_chat::{selector::SpeakerSelector, Participant},
+    model::{
+        openai::OpenAi, ChatModel, ChatParams, ChatRequest, ModelError, ModelMessage, TokenCallback,
+    },
     record::{ChatRecord, Record},
+    tool::ToolRegistry,
     Str,
 };
 use chess::Color;
-use libopenai::{
-    chat::{ChatCompletion, Message, Role},
-    error::Error,
-    Client,
-};
-use std::ops::Deref;
+use futures::channel::mpsc;
+use libopenai::{chat::Role, Client};
+use serde_json::Value;
+use std::collections::HashSet;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-pub enum ChessError {
+pub enum ChatError<E> {
+    #[error("{0}")]
+    Model(#[source] E),
+    #[error("Maximum number of tool rounds ({0}) reached")]
+    MaxToolRounds(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum ChessError<E = ModelError> {
     #[error("{0}")]
-    ChatGpt(#[from] Error),
+    Model(#[source] E),
     #[error("{0}")]
     Chess(#[from] failure::Compat<chess::Error>),
     #[error("No legal move found")]
     NoLegalMoveFound,
 }
 
+#[derive(Debug, Error)]
+pub enum SelectorError<E = ModelError> {
+    #[error("{0}")]
+    Model(#[source] E),
+    #[error("No valid speaker selected")]
+    NoValidSpeaker,
+    #[error("Default speaker `{0}` isn't a participant of the group chat")]
+    InvalidDefault(Str),
+}
+
+/// Chat agent, whose replies are completed by a [`ChatModel`] (OpenAI's API by default)
 #[derive(Debug, Clone)]
-pub struct ChatGPT {
-    pub client: Client,
+pub struct ChatGPT<M = OpenAi> {
+    /// Backend that completes the chat
+    pub backend: M,
     pub model: Str,
+    /// Name of the agent, attached to the messages it writes
+    pub name: Option<Str>,
+    pub description: Option<Str>,
+    /// Tools the model can call before giving it's answer
+    pub tools: ToolRegistry,
+    /// Maximum number of times the model can ask for tool calls before giving it's answer
+    pub max_tool_rounds: usize,
+    pub params: ChatParams,
+    /// Callback that receives the tokens of every reply, as they're streamed
+    pub on_token: Option<TokenCallback>,
 }
 
 impl ChatGPT {
     pub fn new(model: impl Into<Str>, client: Client) -> Self {
+        return Self::with_backend(OpenAi::new(client), model);
+    }
+
+    /// Sets the base URL of the API
+    pub fn with_base_url(mut self, base_url: impl Into<Str>) -> Self {
+        self.backend.base_url = base_url.into();
+        self
+    }
+}
+
+impl<M> ChatGPT<M> {
+    /// Creates a new agent, whose replies are completed by `backend`
+    pub fn with_backend(backend: M, model: impl Into<Str>) -> Self {
         return Self {
-            client,
+            backend,
             model: model.into(),
+            name: None,
+            description: None,
+            tools: ToolRegistry::new(),
+            max_tool_rounds: 10,
+            params: ChatParams::default(),
+            on_token: None,
         };
     }
 
-    pub fn into_chess(self, max_tries: usize) -> ChessGPT {
-        return ChessGPT {
-            client: self.client,
-            model: self.model,
-            max_tries,
+    /// Names the agent.
+    ///
+    /// Named agents attach their name to the messages they write, and only see their own messages as
+    /// [`Assistant`](Role::Assistant) ones (see [`ChatRecord::view_as`]). Unnamed agents taking part in a
+    /// group chat do the same with their participant's name.
+    pub fn with_name(mut self, name: impl Into<Str>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Describes the role of the agent
+    pub fn with_description(mut self, description: impl Into<Str>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the tools the model can call.
+    ///
+    /// Every call, and it's result, is appended to the record before the model gives it's answer.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Sets the maximum number of times the model can ask for tool calls before giving it's answer.
+    ///
+    /// If the model asks for more, the agent fails with [`ChatError::MaxToolRounds`] without calling them.
+    pub fn with_max_tool_rounds(mut self, max_tool_rounds: usize) -> Self {
+        self.max_tool_rounds = max_tool_rounds;
+        self
+    }
+
+    /// Sets the sampling parameters of the completions
+    pub fn with_params(mut self, params: ChatParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Streams the replies of the model, calling `f` with every token as it arrives.
+    ///
+    /// The reply is only pushed to the record once it's complete.
+    pub fn on_token<F: 'static + Send + Sync + Fn(&str)>(mut self, f: F) -> Self {
+        self.on_token = Some(TokenCallback::new(f));
+        self
+    }
+
+    /// Streams the replies of the model, returning the stream their tokens will be sent through.
+    ///
+    /// The reply is only pushed to the record once it's complete. The stream ends once the agent is dropped.
+    pub fn token_stream(self) -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded();
+        let this = self.on_token(move |token| {
+            let _ = sender.unbounded_send(token.to_string());
+        });
+        return (this, receiver);
+    }
+
+    pub fn into_chess(self, max_tries: usize) -> ChessGPT<M> {
+        return ChessGPT::with_backend(self.backend, self.model, max_tries);
+    }
+
+    pub fn into_selector(self, max_tries: usize) -> GptSelector<M> {
+        return GptSelector::with_backend(self.backend, self.model, max_tries);
+    }
+
+    /// Messages of the record as seen by the agent.
+    ///
+    /// Unnamed agents see them as the record's current speaker (like the group chat participant they're playing).
+    /// Tool results are only sent as such when the call they answer was made by this agent.
+    fn messages(&self, record: &ChatRecord) -> Vec<ModelMessage> {
+        let messages = match self.name.as_deref().or(record.speaker()) {
+            Some(name) => record.view_as(name),
+            None => record.messages().to_vec(),
         };
+
+        let mut calls = HashSet::new();
+        return messages
+            .into_iter()
+            .enumerate()
+            .map(|(i, message)| {
+                let mut message = ModelMessage::from(message);
+                let Some(metadata) = record.metadata(i) else {
+                    return message;
+                };
+
+                if matches!(message.role, Role::Assistant) && !metadata.tool_calls.is_empty() {
+                    calls.extend(metadata.tool_calls.iter().map(|call| call.id.as_str()));
+                    message.tool_calls = metadata.tool_calls.clone();
+                } else if let Some(id) = metadata
+                    .tool_call_id
+                    .as_deref()
+                    .filter(|id| calls.contains(id))
+                {
+                    message.tool_call_id = Some(Str::Owned(id.to_string()));
+                }
+                message
+            })
+            .collect();
     }
 }
 
-impl Agent<ChatRecord> for ChatGPT {
-    type Error = Error;
+impl<M: ChatModel> Agent<ChatRecord> for ChatGPT<M> {
+    type Error = ChatError<M::Error>;
 
     async fn handle(&mut self, record: &mut ChatRecord) -> Result<(), Self::Error> {
         return self.handle_ref(record).await;
     }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        return self.name.as_deref();
+    }
+
+    #[inline]
+    fn description(&self) -> Option<&str> {
+        return self.description.as_deref();
+    }
 }
 
-impl AgentRef<ChatRecord> for ChatGPT {
+impl<M: ChatModel> AgentRef<ChatRecord> for ChatGPT<M> {
     async fn handle_ref(&self, record: &mut ChatRecord) -> Result<(), Self::Error> {
-        let mut chat =
-            ChatCompletion::new(self.model.deref(), record.messages().to_vec(), &self.client)
-                .await?;
-        if chat.choices.is_empty() {
-            return Err(Error::msg("No response choices found"));
-        }
+        let name = self.name.as_deref();
+        let mut round = 0;
 
-        record
-            .push_message(chat.choices.swap_remove(0).message)
-            .unwrap();
-        return Ok(());
+        loop {
+            let messages = self.messages(record);
+            let request = ChatRequest::new(&self.model, &messages)
+                .tools(self.tools.tools())
+                .params(&self.params);
+
+            let reply = match self.on_token.as_ref() {
+                Some(on_token) => self.backend.complete_streaming(request, on_token).await,
+                None => self.backend.complete(request).await,
+            }
+            .map_err(ChatError::Model)?;
+
+            if reply.tool_calls.is_empty() {
+                record
+                    .push_named(name, Role::Assistant, reply.content)
+                    .unwrap();
+                return Ok(());
+            }
+
+            // Once every round is used, the calls are rejected before any of them is made
+            if round == self.max_tool_rounds {
+                return Err(ChatError::MaxToolRounds(self.max_tool_rounds));
+            }
+
+            round += 1;
+            record.push_tool_calls(name, reply.content, reply.tool_calls.clone());
+            for call in reply.tool_calls {
+                // Failed calls are reported back to the model, so that it can recover from them
+                let result = match self
+                    .tools
+                    .call(&call.function.name, &call.function.arguments)
+                    .await
+                {
+                    Ok(Value::String(result)) => result,
+                    Ok(result) => result.to_string(),
+                    Err(e) => format!("Error: {e}"),
+                };
+                record.push_tool_result(name, call.id, result);
+            }
+        }
     }
 }
 
 /// Chess-specialized verision of [`ChatGPT`]
-pub struct ChessGPT {
-    pub client: Client,
+pub struct ChessGPT<M = OpenAi> {
+    /// Backend that completes the chat
+    pub backend: M,
     pub model: Str,
     pub max_tries: usize,
 }
 
 impl ChessGPT {
     pub fn new(model: impl Into<Str>, client: Client, max_tries: usize) -> Self {
+        return Self::with_backend(OpenAi::new(client), model, max_tries);
+    }
+}
+
+impl<M> ChessGPT<M> {
+    /// Creates a new agent, whose moves are completed by `backend`
+    pub fn with_backend(backend: M, model: impl Into<Str>, max_tries: usize) -> Self {
         return Self {
-            client,
+            backend,
             model: model.into(),
             max_tries,
         };
     }
 
-    pub fn into_chat(self) -> ChatGPT {
-        return ChatGPT {
-            client: self.client,
-            model: self.model,
-        };
+    pub fn into_chat(self) -> ChatGPT<M> {
+        return ChatGPT::with_backend(self.backend, self.model);
     }
 }
 
-impl Agent<chess::Game> for ChessGPT {
-    type Error = ChessError;
+impl<M: ChatModel> Agent<chess::Game> for ChessGPT<M> {
+    type Error = ChessError<M::Error>;
 
     async fn handle(&mut self, record: &mut chess::Game) -> Result<(), Self::Error> {
         return self.handle_ref(record).await;
     }
 }
 
-impl AgentRef<chess::Game> for ChessGPT {
+impl<M: ChatModel> AgentRef<chess::Game> for ChessGPT<M> {
     async fn handle_ref(&self, record: &mut chess::Game) -> Result<(), Self::Error> {
         let mut is_assistant = record.side_to_move() == Color::White;
         let mut messages = Vec::with_capacity(2 * record.actions().len());
 
         for action in record.actions() {
             if let chess::Action::MakeMove(chess_move) = action {
-                messages.push(Message::new(
+                messages.push(ModelMessage::new(
                     is_assistant
                         .then_some(Role::Assistant)
                         .unwrap_or(Role::User),
@@ -122,34 +313,39 @@ impl AgentRef<chess::Game> for ChessGPT {
         let mut illegal_moves = Vec::with_capacity(self.max_tries);
         for _ in 0..self.max_tries {
             let mut messages = messages.clone();
-            messages.push(Message::system(
-                format!("You're a chess engine. Respond only with the next move to play, based on the previous moves{}, using the UCI format. The current state of the board is {} (using FEN notation).",
-                illegal_moves.is_empty().then(|| format!(" and knowing ({}) are illegal moves.",
-                illegal_moves.join(", "))).unwrap_or_default(),
-                record.current_position().to_string()),
-            ));
+            let illegal = if illegal_moves.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " and knowing ({}) are illegal moves",
+                    illegal_moves.join(", ")
+                )
+            };
 
-            let mut chat =
-                ChatCompletion::new(self.model.deref(), messages.clone(), &self.client).await?;
+            messages.push(ModelMessage::new(
+                Role::System,
+                format!("You're a chess engine. Respond only with the next move to play, based on the previous moves{illegal}, using the UCI format. The current state of the board is {} (using FEN notation).",
+                record.current_position()),
+            ));
 
-            if chat.choices.is_empty() {
-                return Err(ChessError::ChatGpt(Error::msg("No response choices found")));
-            }
+            let reply = self
+                .backend
+                .complete(ChatRequest::new(&self.model, &messages))
+                .await
+                .map_err(ChessError::Model)?;
 
-            let mut msg = chat.choices.swap_remove(0).message;
             // Transform response into valid UCI move format
-            if msg.content.ends_with(|c: char| !c.is_alphanumeric()) {
-                let _ = msg.content.to_mut().pop();
+            let mut content = reply.content;
+            if content.ends_with(|c: char| !c.is_alphanumeric()) {
+                let _ = content.to_mut().pop();
             }
-            msg.content = Str::Owned(
-                msg.content
-                    .split_whitespace()
-                    .last()
-                    .unwrap_or(&msg.content)
-                    .to_string(),
-            );
-
-            match record.push_message(msg) {
+            let content = content
+                .split_whitespace()
+                .last()
+                .unwrap_or(&content)
+                .to_string();
+
+            match record.push(Role::Assistant, content) {
                 Ok(_) => return Ok(()),
                 Err(super::chess::Error::IllegalMove(chess_move)) => {
                     illegal_moves.push(chess_move.to_string());
@@ -164,3 +360,335 @@ impl AgentRef<chess::Game> for ChessGPT {
         return Err(ChessError::NoLegalMoveFound);
     }
 }
+
+/// Group chat speaker selector that asks a [`ChatModel`] who should speak next
+pub struct GptSelector<M = OpenAi> {
+    /// Backend that completes the chat
+    pub backend: M,
+    pub model: Str,
+    pub max_tries: usize,
+    /// Number of recent messages shown to the model
+    pub history: usize,
+    /// Participant that will speak whenever the model fails to choose a valid one
+    pub default: Option<Str>,
+}
+
+impl GptSelector {
+    pub fn new(model: impl Into<Str>, client: Client, max_tries: usize) -> Self {
+        return Self::with_backend(OpenAi::new(client), model, max_tries);
+    }
+}
+
+impl<M> GptSelector<M> {
+    /// Creates a new selector, whose choices are completed by `backend`
+    pub fn with_backend(backend: M, model: impl Into<Str>, max_tries: usize) -> Self {
+        return Self {
+            backend,
+            model: model.into(),
+            max_tries,
+            history: 10,
+            default: None,
+        };
+    }
+
+    /// Sets the number of recent messages shown to the model
+    pub fn history(mut self, history: usize) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Sets the participant that will speak whenever the model fails to choose a valid one
+    pub fn default_speaker(mut self, name: impl Into<Str>) -> Self {
+        self.default = Some(name.into());
+        self
+    }
+}
+
+impl<M: ChatModel> SpeakerSelector<ChatRecord> for GptSelector<M> {
+    async fn select(
+        &mut self,
+        record: &ChatRecord,
+        participants: &[Participant<'_, ChatRecord>],
+        _: usize,
+    ) -> color_eyre::Result<usize> {
+        let roles = participants
+            .iter()
+            .map(|participant| match participant.description() {
+                Some(description) => format!("- {}: {description}", participant.name()),
+                None => format!("- {}", participant.name()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Messages are prefixed by their author, so that the model knows who has already spoken
+        let entries = record.entries().collect::<Vec<_>>();
+        let transcript = entries[entries.len().saturating_sub(self.history)..]
+            .iter()
+            .map(|(author, message)| {
+                let mut message = ModelMessage::from((*message).clone());
+                if let Some(author) = author.filter(|_| !matches!(message.role, Role::System)) {
+                    message.content = Str::Owned(format!("{author}: {}", message.content));
+                }
+                message
+            })
+            .collect::<Vec<_>>();
+
+        let mut invalid_speakers = Vec::with_capacity(self.max_tries);
+        for _ in 0..self.max_tries {
+            let mut messages = transcript.clone();
+            let invalid = if invalid_speakers.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " and knowing ({}) aren't participants of the chat",
+                    invalid_speakers.join(", ")
+                )
+            };
+
+            messages.push(ModelMessage::new(
+                Role::System,
+                format!("You're managing a group chat with the following participants:\n{roles}\nRespond only with the name of the participant that should speak next, based on the previous messages{invalid}."),
+            ));
+
+            let reply = self
+                .backend
+                .complete(ChatRequest::new(&self.model, &messages))
+                .await
+                .map_err(SelectorError::Model)?;
+
+            let name = reply
+                .content
+                .trim_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation());
+
+            match participants
+                .iter()
+                .position(|participant| participant.name().eq_ignore_ascii_case(name))
+            {
+                Some(i) => return Ok(i),
+                None => invalid_speakers.push(name.to_string()),
+            }
+        }
+
+        let Some(default) = self.default.as_deref() else {
+            return Err(SelectorError::<M::Error>::NoValidSpeaker.into());
+        };
+
+        return participants
+            .iter()
+            .position(|participant| participant.name() == default)
+            .ok_or_else(|| {
+                SelectorError::<M::Error>::InvalidDefault(Str::Owned(default.to_string())).into()
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        group_chat::{selector::RoundRobin, GroupChat},
+        model::scripted::{reply, tool_call, ScriptEnded, Scripted},
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Group chat whose participants are only used for their names
+    fn chat() -> GroupChat<'static, ChatRecord, RoundRobin, fn(&ChatRecord, usize) -> bool> {
+        return GroupChat::new(RoundRobin)
+            .participant("coder", "code".to_string())
+            .participant("reviewer", "review".to_string());
+    }
+
+    /// Tools with a single `add` tool, which counts how many times it's called
+    fn counter() -> (ToolRegistry, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let tools = ToolRegistry::new().register(
+            "add",
+            "Adds one to the counter",
+            serde_json::json!({ "type": "object", "properties": {} }),
+            {
+                let count = count.clone();
+                move |_: Value| {
+                    let count = count.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move { Ok::<_, color_eyre::Report>(count) }
+                }
+            },
+        );
+        return (tools, count);
+    }
+
+    #[tokio::test]
+    async fn tool_rounds() {
+        let (tools, count) = counter();
+        let backend = Scripted::new([tool_call("call_1", "add", "{}"), reply("Done")]);
+        let mut record = ChatRecord::new();
+        ChatGPT::with_backend(backend.clone(), "gpt-4")
+            .with_tools(tools)
+            .with_max_tool_rounds(1)
+            .handle(&mut record)
+            .await
+            .unwrap();
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        let contents = record
+            .messages()
+            .iter()
+            .map(|message| message.content.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["", "1", "Done"]);
+
+        // The model receives the result of it's call before answering
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1][0].tool_calls[0].id, "call_1");
+        assert_eq!(requests[1][1].tool_call_id.as_deref(), Some("call_1"));
+    }
+
+    #[tokio::test]
+    async fn max_tool_rounds() {
+        let (tools, count) = counter();
+        let backend = Scripted::new([
+            tool_call("call_1", "add", "{}"),
+            tool_call("call_2", "add", "{}"),
+        ]);
+        let mut record = ChatRecord::new();
+        let e = ChatGPT::with_backend(backend, "gpt-4")
+            .with_tools(tools.clone())
+            .with_max_tool_rounds(1)
+            .handle(&mut record)
+            .await;
+
+        // The second round of calls is rejected before being made, or added to the record
+        assert!(matches!(e, Err(ChatError::MaxToolRounds(1))));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(record.messages().len(), 2);
+
+        let backend = Scripted::new([tool_call("call_1", "add", "{}")]);
+        let mut record = ChatRecord::new();
+        let e = ChatGPT::with_backend(backend.clone(), "gpt-4")
+            .with_tools(tools)
+            .with_max_tool_rounds(0)
+            .handle(&mut record)
+            .await;
+
+        assert!(matches!(e, Err(ChatError::MaxToolRounds(0))));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(record.messages().is_empty());
+        assert_eq!(backend.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unnamed_participants() {
+        let coder = Scripted::new([reply("A"), reply("C")]);
+        let reviewer = Scripted::new([reply("B")]);
+
+        let mut record = ChatRecord::new();
+        GroupChat::new(RoundRobin)
+            .participant("coder", ChatGPT::with_backend(coder.clone(), "gpt-4"))
+            .participant("reviewer", ChatGPT::with_backend(reviewer.clone(), "gpt-4"))
+            .max_rounds(3)
+            .handle(&mut record)
+            .await
+            .unwrap();
+
+        let entries = record
+            .entries()
+            .map(|(author, message)| (author, message.content.as_ref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (Some("coder"), "A"),
+                (Some("reviewer"), "B"),
+                (Some("coder"), "C")
+            ]
+        );
+
+        // Every participant sees it's own messages as the assistant's, and the other's as the user's
+        let view = |messages: &[ModelMessage]| {
+            messages
+                .iter()
+                .map(|message| (message.role, message.content.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            view(&reviewer.requests()[0]).as_slice(),
+            [(Role::User, content)] if content == "coder: A"
+        ));
+        assert!(matches!(
+            view(&coder.requests()[1]).as_slice(),
+            [(Role::Assistant, a), (Role::User, b)] if a == "A" && b == "reviewer: B"
+        ));
+    }
+
+    #[tokio::test]
+    async fn chess_illegal_moves() {
+        let backend = Scripted::new([reply("e2e5"), reply("I'll play e2e4.")]);
+        let mut game = chess::Game::new();
+        ChessGPT::with_backend(backend.clone(), "gpt-4", 3)
+            .handle(&mut game)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            game.actions().as_slice(),
+            [chess::Action::MakeMove(chess_move)] if chess_move.to_string() == "e2e4"
+        ));
+
+        // The model is told about the moves it already tried
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0][0].content.contains("illegal"));
+        assert!(requests[1][0]
+            .content
+            .contains("knowing (e2e5) are illegal moves, using the UCI format"));
+
+        let backend = Scripted::new([reply("e2e5"), reply("e7e5")]);
+        let e = ChessGPT::with_backend(backend, "gpt-4", 2)
+            .handle(&mut chess::Game::new())
+            .await;
+        assert!(matches!(e, Err(ChessError::NoLegalMoveFound)));
+    }
+
+    #[tokio::test]
+    async fn selector_invalid_names() {
+        let chat = chat();
+        let record = ChatRecord::new();
+
+        let backend = Scripted::new([reply(" Reviewer.")]);
+        let mut selector = GptSelector::with_backend(backend, "gpt-4", 3);
+        let speaker = selector.select(&record, chat.participants(), 0).await;
+        assert_eq!(speaker.unwrap(), 1);
+
+        // Once every try is used, the default speaker is chosen
+        let backend = Scripted::new([reply("nobody"), reply("somebody")]);
+        let mut selector =
+            GptSelector::with_backend(backend.clone(), "gpt-4", 2).default_speaker("reviewer");
+        let speaker = selector.select(&record, chat.participants(), 0).await;
+        assert_eq!(speaker.unwrap(), 1);
+
+        let requests = backend.requests();
+        assert!(requests[1][0]
+            .content
+            .contains("knowing (nobody) aren't participants of the chat"));
+
+        let backend = Scripted::new([reply("nobody")]);
+        let mut selector = GptSelector::with_backend(backend, "gpt-4", 1);
+        let e = selector.select(&record, chat.participants(), 0).await;
+        assert!(matches!(
+            e.unwrap_err().downcast_ref::<SelectorError<ScriptEnded>>(),
+            Some(SelectorError::NoValidSpeaker)
+        ));
+
+        let backend = Scripted::new([reply("nobody")]);
+        let mut selector =
+            GptSelector::with_backend(backend, "gpt-4", 1).default_speaker("manager");
+        let e = selector.select(&record, chat.participants(), 0).await;
+        assert!(matches!(
+            e.unwrap_err().downcast_ref::<SelectorError<ScriptEnded>>(),
+            Some(SelectorError::InvalidDefault(name)) if name == "manager"
+        ));
+    }
+}
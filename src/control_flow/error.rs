@@ -1,10 +1,13 @@
+use super::signal::is_signal;
 use crate::{
     agent::{Agent, AgentRef},
     record::Record,
 };
 use std::future::Future;
 
-/// Catches the error of it's parent agent, and handles it
+/// Catches the error of it's parent agent, and handles it.
+///
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're returned without being handled.
 pub struct Catch<A, F> {
     pub(crate) agent: A,
     pub(crate) f: F,
@@ -17,17 +20,20 @@ impl<
         Fut: Future<Output = Result<A2, A::Error>>,
         A2: Agent<R>,
     > Agent<R> for Catch<A, F>
+where
+    A::Error: 'static,
 {
     type Error = color_eyre::Report;
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
-        if let Err(e) = self.agent.handle(record).await {
-            return match (self.f)(e).await {
+        return match self.agent.handle(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(e) => match (self.f)(e).await {
                 Ok(mut agent) => agent.handle(record).await.map_err(Into::into),
                 Err(e) => Err(e.into()),
-            };
-        }
-        return Ok(());
+            },
+        };
     }
 }
 
@@ -38,14 +44,198 @@ impl<
         Fut: Future<Output = Result<A2, A::Error>>,
         A2: Agent<R>,
     > AgentRef<R> for Catch<A, F>
+where
+    A::Error: 'static,
 {
     async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
-        if let Err(e) = self.agent.handle_ref(record).await {
-            return match (self.f)(e).await {
+        return match self.agent.handle_ref(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(e) => match (self.f)(e).await {
                 Ok(mut agent) => agent.handle(record).await.map_err(Into::into),
                 Err(e) => Err(e.into()),
-            };
-        }
-        return Ok(());
+            },
+        };
     }
 }
+
+/// Catches the error of it's parent agent, and handles it with access to the record.
+///
+/// The handler may return a replacement agent to be played, or `Ok(None)` if it resolved the error in place.
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're returned without being handled.
+pub struct CatchWithRecord<A, F> {
+    pub(crate) agent: A,
+    pub(crate) f: F,
+}
+
+impl<
+        R: Record,
+        A: Agent<R>,
+        F: FnMut(&mut R, A::Error) -> Fut,
+        Fut: Future<Output = Result<Option<A2>, A::Error>>,
+        A2: Agent<R>,
+    > Agent<R> for CatchWithRecord<A, F>
+where
+    A::Error: 'static,
+{
+    type Error = color_eyre::Report;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(e) => match (self.f)(record, e).await {
+                Ok(Some(mut agent)) => agent.handle(record).await.map_err(Into::into),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e.into()),
+            },
+        };
+    }
+}
+
+impl<
+        R: Record,
+        A: AgentRef<R>,
+        F: Fn(&mut R, A::Error) -> Fut,
+        Fut: Future<Output = Result<Option<A2>, A::Error>>,
+        A2: Agent<R>,
+    > AgentRef<R> for CatchWithRecord<A, F>
+where
+    A::Error: 'static,
+{
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle_ref(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(e) => match (self.f)(record, e).await {
+                Ok(Some(mut agent)) => agent.handle(record).await.map_err(Into::into),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e.into()),
+            },
+        };
+    }
+}
+
+/// Catches the error of it's parent agent, and resolves it in place, with access to the record.
+///
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're returned without being handled.
+pub struct RecoverWithRecord<A, F> {
+    pub(crate) agent: A,
+    pub(crate) f: F,
+}
+
+impl<
+        R: Record,
+        A: Agent<R>,
+        F: FnMut(&mut R, A::Error) -> Fut,
+        Fut: Future<Output = Result<(), A::Error>>,
+    > Agent<R> for RecoverWithRecord<A, F>
+where
+    A::Error: 'static,
+{
+    type Error = A::Error;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e),
+            Err(e) => (self.f)(record, e).await,
+        };
+    }
+}
+
+impl<
+        R: Record,
+        A: AgentRef<R>,
+        F: Fn(&mut R, A::Error) -> Fut,
+        Fut: Future<Output = Result<(), A::Error>>,
+    > AgentRef<R> for RecoverWithRecord<A, F>
+where
+    A::Error: 'static,
+{
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle_ref(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e),
+            Err(e) => (self.f)(record, e).await,
+        };
+    }
+}
+
+/// Plays it's fallback agent whenever it's parent agent fails.
+///
+/// Unlike [`Catch`], the fallback agent is built once, and reused every time it's needed.
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're returned without playing the fallback.
+pub struct OrElse<A, B> {
+    pub(crate) agent: A,
+    pub(crate) fallback: B,
+}
+
+impl<R: Record, A: Agent<R>, B: Agent<R>> Agent<R> for OrElse<A, B>
+where
+    A::Error: 'static,
+{
+    type Error = color_eyre::Report;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(_) => self.fallback.handle(record).await.map_err(Into::into),
+        };
+    }
+}
+
+impl<R: Record, A: AgentRef<R>, B: AgentRef<R>> AgentRef<R> for OrElse<A, B>
+where
+    A::Error: 'static,
+{
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        return match self.agent.handle_ref(record).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_signal(&e) => Err(e.into()),
+            Err(_) => self.fallback.handle_ref(record).await.map_err(Into::into),
+        };
+    }
+}
+
+/// Always plays it's cleanup agent after it's parent agent, whether the latter succeeded or not.
+///
+/// If the parent agent failed, it's error is the one returned, even if the cleanup agent also fails.
+pub struct Finally<A, C> {
+    pub(crate) agent: A,
+    pub(crate) cleanup: C,
+}
+
+impl<R: Record, A: Agent<R>, C: Agent<R>> Agent<R> for Finally<A, C> {
+    type Error = color_eyre::Report;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let result = self.agent.handle(record).await;
+        let cleanup = self.cleanup.handle(record).await;
+        result.map_err(Into::into)?;
+        return cleanup.map_err(Into::into);
+    }
+}
+
+impl<R: Record, A: AgentRef<R>, C: AgentRef<R>> AgentRef<R> for Finally<A, C> {
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        let result = self.agent.handle_ref(record).await;
+        let cleanup = self.cleanup.handle_ref(record).await;
+        result.map_err(Into::into)?;
+        return cleanup.map_err(Into::into);
+    }
+}
+
+/// Builds a chain of agents, where each one is only played if all the previous ones failed.
+///
+/// `fallback![a, b, c]` is equivalent to `a.or_else(b).or_else(c)`
+#[macro_export]
+macro_rules! fallback {
+    ($agent:expr $(,)?) => {
+        $agent
+    };
+    ($agent:expr, $fallback:expr $(, $rest:expr)* $(,)?) => {
+        $crate::fallback!($crate::agent::Agent::or_else($agent, $fallback) $(, $rest)*)
+    };
+}
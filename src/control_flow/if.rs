@@ -0,0 +1,90 @@
+use crate::{agent::Agent, error::ErrorPolicy, record::Record, Conversation, MainConversation};
+
+/// Plays one of two conversations, depending on the value of a predicate
+pub struct If<'a, R: 'static, F> {
+    pub(crate) predicate: F,
+    pub(crate) then: MainConversation<'a, R>,
+    pub(crate) otherwise: MainConversation<'a, R>,
+}
+
+impl<'a, R: Record, F: FnMut(&mut R) -> bool> Agent<R> for If<'a, R, F> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "if";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        if (self.predicate)(record) {
+            return self
+                .then
+                .play_with(record)
+                .await
+                .map_err(|e| e.in_branch("then").into());
+        }
+        return self
+            .otherwise
+            .play_with(record)
+            .await
+            .map_err(|e| e.in_branch("else").into());
+    }
+}
+
+pub struct IfBuilder<'a, R: 'static, F, P> {
+    pub(crate) parent: P,
+    pub(crate) child: If<'a, R, F>,
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P> Conversation<'a, R>
+    for IfBuilder<'a, R, F, P>
+{
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.child.then = self.child.then.agent(agent);
+        self
+    }
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P: Conversation<'a, R>> IfBuilder<'a, R, F, P> {
+    /// Sets what this branch does when one of it's agents fails
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.child.then = self.child.then.error_policy(policy);
+        self
+    }
+
+    /// Starts the branch that will be played when the predicate doesn't hold
+    pub fn else_branch(self) -> ElseBuilder<'a, R, F, P> {
+        return ElseBuilder {
+            parent: self.parent,
+            child: self.child,
+        };
+    }
+
+    pub fn end_if(self) -> P {
+        return self.parent.agent(self.child);
+    }
+}
+
+pub struct ElseBuilder<'a, R: 'static, F, P> {
+    pub(crate) parent: P,
+    pub(crate) child: If<'a, R, F>,
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P> Conversation<'a, R>
+    for ElseBuilder<'a, R, F, P>
+{
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.child.otherwise = self.child.otherwise.agent(agent);
+        self
+    }
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P: Conversation<'a, R>>
+    ElseBuilder<'a, R, F, P>
+{
+    /// Sets what this branch does when one of it's agents fails
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.child.otherwise = self.child.otherwise.error_policy(policy);
+        self
+    }
+
+    pub fn end_if(self) -> P {
+        return self.parent.agent(self.child);
+    }
+}
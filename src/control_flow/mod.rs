@@ -1,2 +1,11 @@
 pub mod error;
+pub mod r#if;
+pub mod parallel;
+pub mod race;
+pub mod repeat;
+pub mod retry;
+pub mod signal;
+pub mod switch;
+pub mod timeout;
+pub mod transaction;
 pub mod r#while;
@@ -0,0 +1,210 @@
+use crate::{
+    agent::{Agent, DynAgent},
+    error::ConversationError,
+    record::{ChatRecord, Record},
+    Conversation,
+};
+
+/// Strategy used to combine the records of a [`Parallel`] block back into the original one
+pub trait Merge<R> {
+    /// Merges the records of every branch, in the order the branches were declared
+    fn merge(&mut self, record: &mut R, branches: Vec<R>) -> color_eyre::Result<()>;
+}
+
+/// Replaces the record with the one of the first branch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeepFirst;
+
+impl<R> Merge<R> for KeepFirst {
+    fn merge(&mut self, record: &mut R, branches: Vec<R>) -> color_eyre::Result<()> {
+        if let Some(first) = branches.into_iter().next() {
+            *record = first;
+        }
+        return Ok(());
+    }
+}
+
+/// Appends the messages added by every branch (alongside their metadata), one branch after the other
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppendAll;
+
+impl Merge<ChatRecord> for AppendAll {
+    fn merge(
+        &mut self,
+        record: &mut ChatRecord,
+        branches: Vec<ChatRecord>,
+    ) -> color_eyre::Result<()> {
+        let base = record.messages().len();
+        for branch in branches {
+            for i in base..branch.messages().len() {
+                let metadata = branch.metadata(i).cloned().unwrap_or_default();
+                record.push_with_metadata(branch.messages()[i].clone(), metadata);
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// Uses a custom reducer as merge strategy.
+///
+/// Closures can be used directly as merge strategies, this function just helps infering the types of their arguments.
+#[inline]
+pub fn reduce<R, F: FnMut(&mut R, Vec<R>) -> color_eyre::Result<()>>(f: F) -> F {
+    return f;
+}
+
+impl<R, F: FnMut(&mut R, Vec<R>) -> color_eyre::Result<()>> Merge<R> for F {
+    #[inline]
+    fn merge(&mut self, record: &mut R, branches: Vec<R>) -> color_eyre::Result<()> {
+        return (self)(record, branches);
+    }
+}
+
+/// Plays it's agents concurrently, each one with it's own copy of the record
+pub struct Parallel<'a, R: 'static, M> {
+    pub(crate) agents: Vec<DynAgent<'a, R>>,
+    pub(crate) merge: M,
+}
+
+impl<'a, R: Record + Clone, M: Merge<R>> Agent<R> for Parallel<'a, R, M> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "parallel";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let mut branches = vec![record.clone(); self.agents.len()];
+
+        let results = futures::future::join_all(
+            self.agents
+                .iter_mut()
+                .zip(branches.iter_mut())
+                .map(|(agent, branch)| agent.handle(branch)),
+        )
+        .await;
+
+        for (i, result) in results.into_iter().enumerate() {
+            if let Err(e) = result {
+                return Err(ConversationError::at(&self.agents[i], i, e).into());
+            }
+        }
+
+        return self.merge.merge(record, branches);
+    }
+}
+
+pub struct ParallelBuilder<'a, R: 'static, M, P> {
+    pub(crate) parent: P,
+    pub(crate) child: Parallel<'a, R, M>,
+}
+
+impl<'a, R: Record + Clone, M: 'a + Merge<R>, P> Conversation<'a, R>
+    for ParallelBuilder<'a, R, M, P>
+{
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.child.agents.push(DynAgent::from_agent(agent));
+        self
+    }
+}
+
+impl<'a, R: Record + Clone, M: 'a + Merge<R>, P: Conversation<'a, R>> ParallelBuilder<'a, R, M, P> {
+    pub fn end_parallel(self) -> P {
+        return self.parent.agent(self.child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fail, say, Delayed, Log},
+        MainConversation,
+    };
+    use libopenai::chat::Role;
+
+    #[tokio::test]
+    async fn keep_first() {
+        let log = MainConversation::<Log>::new()
+            .agent(say("start"))
+            .parallel(KeepFirst)
+            .agent(Delayed {
+                millis: 20,
+                content: "slow",
+            })
+            .agent(say("fast"))
+            .end_parallel()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["start", "slow"]);
+    }
+
+    #[tokio::test]
+    async fn append_all() {
+        let mut record = ChatRecord::new();
+        record.push(Role::User, "start").unwrap();
+
+        MainConversation::new()
+            .parallel(AppendAll)
+            .agent(Delayed {
+                millis: 20,
+                content: "slow",
+            })
+            .agent(
+                MainConversation::new()
+                    .agent(say("fast"))
+                    .agent(say("faster")),
+            )
+            .end_parallel()
+            .play_with(&mut record)
+            .await
+            .unwrap();
+
+        // Branches are appended in the order they were declared, not in the one they finished
+        let messages = record
+            .messages()
+            .iter()
+            .map(|message| message.content.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["start", "slow", "fast", "faster"]);
+    }
+
+    #[tokio::test]
+    async fn custom_reduce() {
+        let log = MainConversation::<Log>::new()
+            .agent(say("start"))
+            .parallel(reduce(|log: &mut Log, branches: Vec<Log>| {
+                for branch in branches {
+                    log.0.push(branch.0.join("+"));
+                }
+                Ok(())
+            }))
+            .agent(say("a"))
+            .agent(say("b"))
+            .end_parallel()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["start", "start+a", "start+b"]);
+    }
+
+    #[tokio::test]
+    async fn branch_error() {
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .parallel(KeepFirst)
+            .agent(say("a"))
+            .agent(fail("boom"))
+            .end_parallel()
+            .play_with(&mut record)
+            .await
+            .unwrap_err();
+
+        // Records aren't merged if any branch fails
+        assert!(record.0.is_empty());
+        assert_eq!(
+            e.to_string(),
+            "Agent at `parallel[0]/agent[1]` failed: boom"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use super::signal::is_signal;
+use crate::{
+    agent::{Agent, DynAgent},
+    error::ConversationError,
+    record::Record,
+};
+use futures::{stream::FuturesUnordered, StreamExt};
+use thiserror::Error;
+
+/// Error returned when every agent of a [`Race`] fails
+#[derive(Debug, Error)]
+#[error("All {} racing agents failed", .errors.len())]
+pub struct RaceError {
+    /// Errors of every agent (as [`ConversationError`]s), in the order they failed
+    pub errors: Vec<color_eyre::Report>,
+}
+
+/// Plays it's agents concurrently, each one with it's own copy of the record,
+/// and keeps the record of the first one to succeed.
+///
+/// The remaining agents are cancelled as soon as one of them succeeds, or returns a
+/// [`ControlSignal`](super::signal::ControlSignal). In the latter case, the record of the agent is kept,
+/// and it's signal is returned.
+pub struct Race<'a, R: 'static> {
+    pub(crate) agents: Vec<DynAgent<'a, R>>,
+}
+
+/// Creates a race between the specified agents
+pub fn race<'a, R: Record, A: 'a + Agent<R>>(agents: impl IntoIterator<Item = A>) -> Race<'a, R> {
+    return Race {
+        agents: agents.into_iter().map(DynAgent::from_agent).collect(),
+    };
+}
+
+impl<'a, R> Race<'a, R> {
+    pub fn new() -> Self {
+        return Self { agents: Vec::new() };
+    }
+}
+
+impl<'a, R> Default for Race<'a, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, R: Record> Race<'a, R> {
+    /// Adds another agent to the race
+    pub fn race_with<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.agents.push(DynAgent::from_agent(agent));
+        self
+    }
+}
+
+impl<'a, R: Record + Clone> Agent<R> for Race<'a, R> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "race";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let mut branches = vec![record.clone(); self.agents.len()];
+        let mut errors = Vec::new();
+
+        // Racers that return a control signal stop the race, as if they had won it
+        let winner = {
+            let mut pending = self
+                .agents
+                .iter_mut()
+                .zip(branches.iter_mut())
+                .enumerate()
+                .map(|(i, (agent, branch))| async move { (i, agent.handle(branch).await) })
+                .collect::<FuturesUnordered<_>>();
+
+            loop {
+                match pending.next().await {
+                    Some((i, Ok(()))) => break Some((i, None)),
+                    Some((i, Err(e))) if is_signal(&e) => break Some((i, Some(e))),
+                    Some((i, Err(e))) => errors.push((i, e)),
+                    None => break None,
+                }
+            }
+        };
+
+        return match winner {
+            Some((i, signal)) => {
+                *record = branches.swap_remove(i);
+                match signal {
+                    Some(signal) => Err(ConversationError::at(&self.agents[i], i, signal).into()),
+                    None => Ok(()),
+                }
+            }
+            None => Err(RaceError {
+                errors: errors
+                    .into_iter()
+                    .map(|(i, e)| ConversationError::at(&self.agents[i], i, e).into())
+                    .collect(),
+            }
+            .into()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        control_flow::signal::Break,
+        testing::{fail, say, Delayed, Failure, Log},
+        Conversation, MainConversation,
+    };
+
+    #[tokio::test]
+    async fn first_success() {
+        let log = MainConversation::<Log>::new()
+            .agent(say("start"))
+            .agent(
+                Race::new()
+                    .race_with(Delayed {
+                        millis: 500,
+                        content: "slow",
+                    })
+                    .race_with(fail("boom"))
+                    .race_with(Delayed {
+                        millis: 10,
+                        content: "fast",
+                    }),
+            )
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["start", "fast"]);
+    }
+
+    #[tokio::test]
+    async fn every_agent_fails() {
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .agent(race([fail("first"), fail("second")]))
+            .play_with(&mut record)
+            .await
+            .unwrap_err();
+
+        assert!(record.0.is_empty());
+        assert_eq!(e.path()[0].kind, "race");
+
+        let errors = &e.downcast_ref::<RaceError>().unwrap().errors;
+        let errors = errors
+            .iter()
+            .map(|e| {
+                let e = e.downcast_ref::<ConversationError>().unwrap();
+                (e.path()[0].index, e.downcast_ref::<Failure>().unwrap().0)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, [(0, "first"), (1, "second")]);
+    }
+
+    #[tokio::test]
+    async fn signal_stops_race() {
+        let log = MainConversation::<Log>::new()
+            .repeat(3)
+            .agent(
+                Race::new()
+                    .race_with(Delayed {
+                        millis: 500,
+                        content: "slow",
+                    })
+                    .race_with(MainConversation::new().agent(say("a")).agent(Break::new())),
+            )
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["a"]);
+
+        // Signals that aren't meant for an enclosing loop are returned, and not buried in a `RaceError`
+        let e = MainConversation::<Log>::new()
+            .agent(
+                Race::new()
+                    .race_with(fail("boom"))
+                    .race_with(Break::labelled("missing")),
+            )
+            .play()
+            .await
+            .unwrap_err();
+
+        assert!(is_signal(&e));
+        assert_eq!(
+            e.to_string(),
+            "Agent at `race[0]/agent[1]` failed: `break` signal outside of a matching loop (`missing`)"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use super::signal::{intercept, Flow};
+use crate::{
+    agent::Agent,
+    error::{ConversationError, ErrorPolicy},
+    record::Record,
+    Conversation, MainConversation, Str,
+};
+
+/// Plays a conversation a fixed number of times
+pub struct Repeat<'a, R: 'static> {
+    pub(crate) times: usize,
+    pub(crate) conversation: MainConversation<'a, R>,
+    pub(crate) label: Option<Str>,
+}
+
+impl<'a, R: Record> Agent<R> for Repeat<'a, R> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "repeat";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let mut errors = Vec::new();
+
+        for iteration in 0..self.times {
+            let result = self.conversation.play_with(record).await;
+            let flow = intercept(
+                result,
+                self.label.as_deref(),
+                iteration,
+                self.conversation.policy(),
+                &mut errors,
+            )?;
+
+            if let Flow::Break = flow {
+                break;
+            }
+        }
+
+        return match ConversationError::collect(errors) {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        };
+    }
+}
+
+pub struct RepeatBuilder<'a, R: 'static, P> {
+    pub(crate) parent: P,
+    pub(crate) child: Repeat<'a, R>,
+}
+
+impl<'a, R: Record, P> Conversation<'a, R> for RepeatBuilder<'a, R, P> {
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.child.conversation = self.child.conversation.agent(agent);
+        self
+    }
+}
+
+impl<'a, R: Record, P: Conversation<'a, R>> RepeatBuilder<'a, R, P> {
+    /// Labels the loop, so that [`ControlSignal`](super::signal::ControlSignal)s can target it from within nested loops
+    pub fn label(mut self, label: impl Into<Str>) -> Self {
+        self.child.label = Some(label.into());
+        self
+    }
+
+    /// Sets what the loop's conversation does when one of it's agents fails.
+    ///
+    /// If the policy is [`Collect`](ErrorPolicy::Collect), the loop keeps going after an iteration fails,
+    /// and returns every error once it has finished.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.child.conversation = self.child.conversation.error_policy(policy);
+        self
+    }
+
+    pub fn end_repeat(self) -> P {
+        return self.parent.agent(self.child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fail, say, Log};
+
+    #[tokio::test]
+    async fn repeat() {
+        let log = MainConversation::<Log>::new()
+            .repeat(3)
+            .agent(say("a"))
+            .end_repeat()
+            .repeat(0)
+            .agent(say("b"))
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["a", "a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn error_iteration() {
+        let e = MainConversation::<Log>::new()
+            .agent(say("a"))
+            .repeat(3)
+            .agent(say("b"))
+            .if_then(|log| log.0.len() == 3)
+            .agent(fail("boom"))
+            .end_if()
+            .end_repeat()
+            .play()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            e.to_string(),
+            "Agent at `repeat[1]#1/if[1]:then/agent[0]` failed: boom"
+        );
+        assert_eq!(e.iteration(), Some(1));
+        assert_eq!(e.path().len(), 3);
+    }
+}
@@ -0,0 +1,150 @@
+use super::signal::is_signal;
+use crate::{
+    agent::{Agent, AgentRef},
+    record::Record,
+};
+use rand::Rng;
+use std::time::Duration;
+
+/// Strategy used to compute the delay between retries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// Waits the same amount of time between every attempt
+    Fixed(Duration),
+    /// Multiplies the delay by `factor` after every attempt, up to `max`
+    Exponential {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+    /// Waits a random amount of time between zero and the equivalent [`Exponential`](Backoff::Exponential) delay
+    Jittered {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// Delay to wait after the `retry`-th failed attempt (starting at zero)
+    pub fn delay(&self, retry: usize) -> Duration {
+        return match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential {
+                initial,
+                factor,
+                max,
+            } => exponential(initial, factor, max, retry),
+            Self::Jittered {
+                initial,
+                factor,
+                max,
+            } => exponential(initial, factor, max, retry).mul_f64(rand::thread_rng().gen()),
+        };
+    }
+}
+
+fn exponential(initial: Duration, factor: f64, max: Duration, retry: usize) -> Duration {
+    let secs = initial.as_secs_f64() * factor.powi(retry.min(i32::MAX as usize) as i32);
+    return Duration::try_from_secs_f64(secs.min(max.as_secs_f64())).unwrap_or(max);
+}
+
+/// Defines how many times, and how often, a failing agent is re-run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: usize,
+    pub backoff: Backoff,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize, backoff: Backoff) -> Self {
+        return Self {
+            max_attempts,
+            backoff,
+        };
+    }
+
+    pub fn fixed(max_attempts: usize, delay: Duration) -> Self {
+        return Self::new(max_attempts, Backoff::Fixed(delay));
+    }
+
+    /// Doubles the delay after every attempt, up to a minute
+    pub fn exponential(max_attempts: usize, initial: Duration) -> Self {
+        return Self::new(
+            max_attempts,
+            Backoff::Exponential {
+                initial,
+                factor: 2.0,
+                max: Duration::from_secs(60),
+            },
+        );
+    }
+
+    /// Doubles the maximum delay after every attempt, up to a minute, and waits a random fraction of it
+    pub fn jittered(max_attempts: usize, initial: Duration) -> Self {
+        return Self::new(
+            max_attempts,
+            Backoff::Jittered {
+                initial,
+                factor: 2.0,
+                max: Duration::from_secs(60),
+            },
+        );
+    }
+}
+
+/// Re-runs it's parent agent whenever it fails with a retryable error.
+///
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're never retried.
+pub struct Retry<A, F> {
+    pub(crate) agent: A,
+    pub(crate) policy: RetryPolicy,
+    pub(crate) predicate: F,
+}
+
+impl<R: Record, A: Agent<R>, F: FnMut(&A::Error) -> bool> Agent<R> for Retry<A, F>
+where
+    A::Error: 'static,
+{
+    type Error = A::Error;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let mut retry = 0;
+        loop {
+            match self.agent.handle(record).await {
+                Err(e)
+                    if retry + 1 < self.policy.max_attempts
+                        && !is_signal(&e)
+                        && (self.predicate)(&e) =>
+                {
+                    tokio::time::sleep(self.policy.backoff.delay(retry)).await;
+                    retry += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+impl<R: Record, A: AgentRef<R>, F: Fn(&A::Error) -> bool> AgentRef<R> for Retry<A, F>
+where
+    A::Error: 'static,
+{
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        let mut retry = 0;
+        loop {
+            match self.agent.handle_ref(record).await {
+                Err(e)
+                    if retry + 1 < self.policy.max_attempts
+                        && !is_signal(&e)
+                        && (self.predicate)(&e) =>
+                {
+                    tokio::time::sleep(self.policy.backoff.delay(retry)).await;
+                    retry += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
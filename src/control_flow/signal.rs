@@ -0,0 +1,337 @@
+use super::timeout::TimeoutError;
+use crate::{
+    agent::{Agent, AgentRef},
+    error::{ConversationError, ErrorPolicy},
+    record::Record,
+    Str,
+};
+use std::any::Any;
+use thiserror::Error;
+
+/// Signal that alters the flow of the nearest enclosing loop, or of the loop with the matching label.
+///
+/// Signals travel through the conversation as errors, until they reach a loop that handles them.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ControlSignal {
+    #[error("`break` signal outside of a matching loop{}", fmt_label(.0))]
+    Break(Option<Str>),
+    #[error("`continue` signal outside of a matching loop{}", fmt_label(.0))]
+    Continue(Option<Str>),
+}
+
+impl ControlSignal {
+    /// Label of the loop targeted by this signal, if any
+    pub fn label(&self) -> Option<&str> {
+        return match self {
+            Self::Break(label) | Self::Continue(label) => label.as_deref(),
+        };
+    }
+
+    /// Checks whether this signal targets a loop with the specified label
+    pub fn targets(&self, label: Option<&str>) -> bool {
+        return match self.label() {
+            Some(target) => Some(target) == label,
+            None => true,
+        };
+    }
+}
+
+/// Checks whether the error is a [`ControlSignal`] (possibly wrapped by a conversation, or by a timeout).
+///
+/// Agents that handle errors let signals through untouched, since they aren't failures.
+pub(crate) fn is_signal<E: 'static>(error: &E) -> bool {
+    let error = error as &dyn Any;
+    if error.is::<ControlSignal>() {
+        return true;
+    }
+    if let Some(e) = error.downcast_ref::<ConversationError>() {
+        return report_is_signal(e.report());
+    }
+    if let Some(e) = error.downcast_ref::<color_eyre::Report>() {
+        return report_is_signal(e);
+    }
+
+    // Timeouts keep the error of their agent, which may be a signal
+    return timeout_is_signal::<ControlSignal>(error)
+        || timeout_is_signal::<ConversationError>(error)
+        || timeout_is_signal::<color_eyre::Report>(error);
+}
+
+fn timeout_is_signal<E: 'static>(error: &dyn Any) -> bool {
+    return match error.downcast_ref::<TimeoutError<E>>() {
+        Some(TimeoutError::Agent(e)) => is_signal(e),
+        _ => false,
+    };
+}
+
+fn report_is_signal(report: &color_eyre::Report) -> bool {
+    if report.downcast_ref::<ControlSignal>().is_some() {
+        return true;
+    }
+    return report
+        .downcast_ref::<ConversationError>()
+        .is_some_and(|e| report_is_signal(e.report()));
+}
+
+fn fmt_label(label: &Option<Str>) -> String {
+    return label
+        .as_deref()
+        .map(|label| format!(" (`{label}`)"))
+        .unwrap_or_default();
+}
+
+/// What a loop should do after playing one of it's iterations
+pub(crate) enum Flow {
+    Next,
+    Break,
+}
+
+/// Intercepts the control signals targeting the loop with the specified label.
+///
+/// If the loop collects it's errors, they're stored in `errors` instead of being returned
+pub(crate) fn intercept(
+    result: Result<(), ConversationError>,
+    label: Option<&str>,
+    iteration: usize,
+    policy: ErrorPolicy,
+    errors: &mut Vec<ConversationError>,
+) -> Result<Flow, ConversationError> {
+    let Err(e) = result else {
+        return Ok(Flow::Next);
+    };
+
+    let mut e = e.in_iteration(iteration);
+    let flow = match e.downcast_ref::<ControlSignal>() {
+        Some(signal @ ControlSignal::Break(_)) if signal.targets(label) => Flow::Break,
+        Some(signal @ ControlSignal::Continue(_)) if signal.targets(label) => Flow::Next,
+        None if policy == ErrorPolicy::Collect => {
+            errors.push(e);
+            return Ok(Flow::Next);
+        }
+        _ => {
+            let errors = std::mem::take(errors);
+            return Err(ConversationError::collect(std::iter::once(e).chain(errors)).unwrap());
+        }
+    };
+
+    errors.extend(e.take_others());
+    return Ok(flow);
+}
+
+/// Agent that breaks out of the nearest enclosing loop, or of the loop with the specified label
+#[derive(Debug, Clone, Default)]
+pub struct Break {
+    pub label: Option<Str>,
+}
+
+impl Break {
+    pub fn new() -> Self {
+        return Self { label: None };
+    }
+
+    pub fn labelled(label: impl Into<Str>) -> Self {
+        return Self {
+            label: Some(label.into()),
+        };
+    }
+}
+
+impl<R: Record> Agent<R> for Break {
+    type Error = ControlSignal;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return self.handle_ref(record).await;
+    }
+}
+
+impl<R: Record> AgentRef<R> for Break {
+    async fn handle_ref(&self, _: &mut R) -> Result<(), Self::Error> {
+        return Err(ControlSignal::Break(self.label.clone()));
+    }
+}
+
+/// Agent that skips to the next iteration of the nearest enclosing loop, or of the loop with the specified label
+#[derive(Debug, Clone, Default)]
+pub struct Continue {
+    pub label: Option<Str>,
+}
+
+impl Continue {
+    pub fn new() -> Self {
+        return Self { label: None };
+    }
+
+    pub fn labelled(label: impl Into<Str>) -> Self {
+        return Self {
+            label: Some(label.into()),
+        };
+    }
+}
+
+impl<R: Record> Agent<R> for Continue {
+    type Error = ControlSignal;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return self.handle_ref(record).await;
+    }
+}
+
+impl<R: Record> AgentRef<R> for Continue {
+    async fn handle_ref(&self, _: &mut R) -> Result<(), Self::Error> {
+        return Err(ControlSignal::Continue(self.label.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{fail, say, Failure, Log},
+        Conversation, MainConversation,
+    };
+
+    #[tokio::test]
+    async fn break_nearest_loop() {
+        let log = MainConversation::<Log>::new()
+            .repeat(2)
+            .agent(say("outer"))
+            .repeat(3)
+            .agent(say("inner"))
+            .agent(Break::new())
+            .end_repeat()
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["outer", "inner", "outer", "inner"]);
+    }
+
+    #[tokio::test]
+    async fn break_labelled_loop() {
+        let log = MainConversation::<Log>::new()
+            .repeat(2)
+            .label("outer")
+            .agent(say("outer"))
+            .repeat(3)
+            .agent(say("inner"))
+            .if_then(|log| log.0.len() == 3)
+            .agent(Break::labelled("outer"))
+            .end_if()
+            .end_repeat()
+            .agent(say("unreachable"))
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["outer", "inner", "inner"]);
+    }
+
+    #[tokio::test]
+    async fn continue_loop() {
+        let log = MainConversation::<Log>::new()
+            .repeat(2)
+            .label("outer")
+            .repeat(2)
+            .agent(say("a"))
+            .agent(Continue::labelled("outer"))
+            .end_repeat()
+            .agent(say("unreachable"))
+            .end_repeat()
+            .repeat(2)
+            .agent(say("b"))
+            .continue_if(|_| true)
+            .agent(say("unreachable"))
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["a", "a", "b", "b"]);
+    }
+
+    #[tokio::test]
+    async fn signal_without_matching_loop() {
+        let e = MainConversation::<Log>::new()
+            .repeat(2)
+            .agent(say("a"))
+            .agent(Break::labelled("missing"))
+            .end_repeat()
+            .play()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            e.downcast_ref::<ControlSignal>(),
+            Some(&ControlSignal::Break(Some(Str::Borrowed("missing"))))
+        );
+        assert_eq!(e.to_string(), "Agent at `repeat[0]#0/agent[1]` failed: `break` signal outside of a matching loop (`missing`)");
+        assert!(is_signal(&e));
+        assert!(is_signal(&e.into_report()));
+    }
+
+    #[tokio::test]
+    async fn collected_errors() {
+        let e = MainConversation::<Log>::new()
+            .repeat(3)
+            .error_policy(ErrorPolicy::Collect)
+            .agent(fail("first"))
+            .agent(say("a"))
+            .agent(fail("second"))
+            .break_if(|log| log.0.len() == 2)
+            .end_repeat()
+            .play()
+            .await
+            .unwrap_err();
+
+        // Errors found before the `break` are kept, in order
+        let errors = e
+            .errors()
+            .map(|e| (e.to_string(), e.iteration()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (
+                    "Agent at `repeat[0]#0/agent[0]` failed: first (and 3 more errors)".to_string(),
+                    Some(0)
+                ),
+                (
+                    "Agent at `repeat[0]#0/agent[2]` failed: second".to_string(),
+                    Some(0)
+                ),
+                (
+                    "Agent at `repeat[0]#1/agent[0]` failed: first".to_string(),
+                    Some(1)
+                ),
+                (
+                    "Agent at `repeat[0]#1/agent[2]` failed: second".to_string(),
+                    Some(1)
+                ),
+            ]
+        );
+        assert_eq!(e.downcast_ref::<Failure>(), Some(&Failure("first")));
+        assert!(!is_signal(&e));
+    }
+
+    #[tokio::test]
+    async fn uncaught_signal_keeps_collected_errors() {
+        let e = MainConversation::<Log>::new()
+            .repeat(2)
+            .error_policy(ErrorPolicy::Collect)
+            .agent(fail("first"))
+            .agent(Continue::labelled("missing"))
+            .end_repeat()
+            .play()
+            .await
+            .unwrap_err();
+
+        assert!(e.downcast_ref::<ControlSignal>().is_some());
+        assert_eq!(e.others().len(), 1);
+        assert_eq!(
+            e.others()[0].downcast_ref::<Failure>(),
+            Some(&Failure("first"))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{agent::Agent, error::ErrorPolicy, record::Record, Conversation, MainConversation};
+
+/// Plays the conversation whose key matches the one returned by the selector
+pub struct Switch<'a, R: 'static, F, K> {
+    pub(crate) selector: F,
+    pub(crate) cases: Vec<(K, MainConversation<'a, R>)>,
+    pub(crate) default: Option<MainConversation<'a, R>>,
+}
+
+impl<'a, R: Record, F: FnMut(&R) -> K, K: PartialEq> Agent<R> for Switch<'a, R, F, K> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "switch";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let key = (self.selector)(record);
+
+        if let Some(i) = self.cases.iter().position(|(case, _)| *case == key) {
+            return self.cases[i]
+                .1
+                .play_with(record)
+                .await
+                .map_err(|e| e.in_branch(format!("case[{i}]")).into());
+        }
+
+        if let Some(conversation) = self.default.as_mut() {
+            return conversation
+                .play_with(record)
+                .await
+                .map_err(|e| e.in_branch("default").into());
+        }
+
+        return Ok(());
+    }
+}
+
+pub struct SwitchBuilder<'a, R: 'static, F, K, P> {
+    pub(crate) parent: P,
+    pub(crate) child: Switch<'a, R, F, K>,
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&R) -> K, K: 'a + PartialEq, P: Conversation<'a, R>>
+    SwitchBuilder<'a, R, F, K, P>
+{
+    /// Starts the arm that will be played when the selector returns `key`
+    pub fn case(self, key: K) -> CaseBuilder<'a, R, F, K, P> {
+        return CaseBuilder {
+            parent: self,
+            key,
+            conversation: MainConversation::new(),
+        };
+    }
+
+    /// Starts the arm that will be played when no other arm matches
+    pub fn default(mut self) -> DefaultBuilder<'a, R, F, K, P> {
+        self.child.default = Some(MainConversation::new());
+        return DefaultBuilder { parent: self };
+    }
+
+    pub fn end_switch(self) -> P {
+        return self.parent.agent(self.child);
+    }
+}
+
+pub struct CaseBuilder<'a, R: 'static, F, K, P> {
+    pub(crate) parent: SwitchBuilder<'a, R, F, K, P>,
+    pub(crate) key: K,
+    pub(crate) conversation: MainConversation<'a, R>,
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&R) -> K, K: 'a + PartialEq, P> Conversation<'a, R>
+    for CaseBuilder<'a, R, F, K, P>
+{
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.conversation = self.conversation.agent(agent);
+        self
+    }
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&R) -> K, K: 'a + PartialEq, P: Conversation<'a, R>>
+    CaseBuilder<'a, R, F, K, P>
+{
+    /// Sets what this arm does when one of it's agents fails
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.conversation = self.conversation.error_policy(policy);
+        self
+    }
+
+    /// Starts the arm that will be played when the selector returns `key`
+    pub fn case(self, key: K) -> CaseBuilder<'a, R, F, K, P> {
+        return self.finish().case(key);
+    }
+
+    /// Starts the arm that will be played when no other arm matches
+    pub fn default(self) -> DefaultBuilder<'a, R, F, K, P> {
+        return self.finish().default();
+    }
+
+    pub fn end_switch(self) -> P {
+        return self.finish().end_switch();
+    }
+
+    fn finish(mut self) -> SwitchBuilder<'a, R, F, K, P> {
+        self.parent.child.cases.push((self.key, self.conversation));
+        return self.parent;
+    }
+}
+
+pub struct DefaultBuilder<'a, R: 'static, F, K, P> {
+    pub(crate) parent: SwitchBuilder<'a, R, F, K, P>,
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&R) -> K, K: 'a + PartialEq, P> Conversation<'a, R>
+    for DefaultBuilder<'a, R, F, K, P>
+{
+    fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
+        self.parent.child.default = self
+            .parent
+            .child
+            .default
+            .take()
+            .map(|conversation| conversation.agent(agent));
+        self
+    }
+}
+
+impl<'a, R: Record, F: 'a + FnMut(&R) -> K, K: 'a + PartialEq, P: Conversation<'a, R>>
+    DefaultBuilder<'a, R, F, K, P>
+{
+    /// Sets what this arm does when one of it's agents fails
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.parent.child.default = self
+            .parent
+            .child
+            .default
+            .take()
+            .map(|conversation| conversation.error_policy(policy));
+        self
+    }
+
+    pub fn end_switch(self) -> P {
+        return self.parent.end_switch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fail, say, Log};
+
+    #[tokio::test]
+    async fn arms() {
+        let log = MainConversation::<Log>::new()
+            .repeat(3)
+            .switch(|log: &Log| log.0.len())
+            .case(0)
+            .agent(say("zero"))
+            .case(1)
+            .agent(say("one"))
+            .default()
+            .agent(say("other"))
+            .end_switch()
+            .end_repeat()
+            .switch(|_: &Log| "missing")
+            .case("present")
+            .agent(say("unreachable"))
+            .end_switch()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["zero", "one", "other"]);
+    }
+
+    #[tokio::test]
+    async fn branch_path() {
+        let e = MainConversation::<Log>::new()
+            .switch(|_: &Log| 2)
+            .case(1)
+            .agent(fail("unreachable"))
+            .default()
+            .agent(say("a"))
+            .agent(fail("boom"))
+            .end_switch()
+            .play()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Agent at `switch[0]:default/agent[1]` failed: boom"
+        );
+
+        let e = MainConversation::<Log>::new()
+            .switch(|_: &Log| 1)
+            .case(0)
+            .agent(say("unreachable"))
+            .case(1)
+            .agent(fail("boom"))
+            .end_switch()
+            .play()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Agent at `switch[0]:case[1]/agent[0]` failed: boom"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    agent::{Agent, AgentRef},
+    record::Record,
+};
+use std::{fmt::Display, time::Duration};
+use thiserror::Error;
+
+/// Error returned when an agent, or a conversation, doesn't finish in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Timed out after {0:?}")]
+pub struct Elapsed(pub Duration);
+
+/// Error returned by a [`Timeout`] agent
+#[derive(Debug)]
+pub enum TimeoutError<E> {
+    /// The agent didn't finish in time, and was cancelled
+    Elapsed(Elapsed),
+    /// The agent finished in time, but failed.
+    ///
+    /// [`ControlSignal`](super::signal::ControlSignal)s returned by the agent are wrapped here too, and are still
+    /// recognized as such by the agents that handle errors (like [`Retry`](super::retry::Retry)).
+    Agent(E),
+}
+
+impl<E: Display> Display for TimeoutError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Self::Elapsed(e) => e.fmt(f),
+            Self::Agent(e) => e.fmt(f),
+        };
+    }
+}
+
+impl<E: Into<color_eyre::Report>> From<TimeoutError<E>> for color_eyre::Report {
+    fn from(value: TimeoutError<E>) -> Self {
+        return match value {
+            TimeoutError::Elapsed(e) => e.into(),
+            TimeoutError::Agent(e) => e.into(),
+        };
+    }
+}
+
+/// Cancels it's parent agent if it doesn't finish in time
+pub struct Timeout<A> {
+    pub(crate) agent: A,
+    pub(crate) duration: Duration,
+}
+
+impl<R: Record, A: Agent<R>> Agent<R> for Timeout<A> {
+    type Error = TimeoutError<A::Error>;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        return match tokio::time::timeout(self.duration, self.agent.handle(record)).await {
+            Ok(result) => result.map_err(TimeoutError::Agent),
+            Err(_) => Err(TimeoutError::Elapsed(Elapsed(self.duration))),
+        };
+    }
+}
+
+impl<R: Record, A: AgentRef<R>> AgentRef<R> for Timeout<A> {
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        return match tokio::time::timeout(self.duration, self.agent.handle_ref(record)).await {
+            Ok(result) => result.map_err(TimeoutError::Agent),
+            Err(_) => Err(TimeoutError::Elapsed(Elapsed(self.duration))),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        control_flow::{retry::RetryPolicy, signal::Break},
+        testing::{say, Delayed, Log},
+        Conversation, MainConversation,
+    };
+
+    /// Conversation that pushes a message, and then breaks out of it's loop
+    fn say_and_break() -> MainConversation<'static, Log> {
+        return MainConversation::new().agent(say("a")).agent(Break::new());
+    }
+
+    #[tokio::test]
+    async fn elapsed() {
+        let mut record = Log::default();
+        let delayed = Delayed {
+            millis: 500,
+            content: "slow",
+        };
+        let result = Agent::<Log>::timeout(delayed, Duration::from_millis(20))
+            .handle(&mut record)
+            .await;
+
+        assert!(matches!(result, Err(TimeoutError::Elapsed(Elapsed(d))) if d.as_millis() == 20));
+        assert!(record.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn signals_through_timeout() {
+        let log = MainConversation::<Log>::new()
+            .repeat(2)
+            .agent(
+                say_and_break()
+                    .timeout(Duration::from_secs(1))
+                    .retry(RetryPolicy::fixed(3, Duration::ZERO)),
+            )
+            .end_repeat()
+            .repeat(2)
+            .agent(
+                say_and_break()
+                    .timeout(Duration::from_secs(1))
+                    .catch(|_| async { Ok(say("caught")) }),
+            )
+            .end_repeat()
+            .repeat(2)
+            .agent(
+                say_and_break()
+                    .timeout(Duration::from_secs(1))
+                    .transactional(),
+            )
+            .end_repeat()
+            .play()
+            .await
+            .unwrap();
+
+        // The signal isn't retried, caught nor rolled back, and breaks out of every loop
+        assert_eq!(log.0, ["a", "a", "a"]);
+    }
+}
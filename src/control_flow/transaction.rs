@@ -0,0 +1,117 @@
+use super::signal::is_signal;
+use crate::{
+    agent::{Agent, AgentRef},
+    record::Snapshot,
+};
+
+/// Restores the record to it's previous state whenever it's parent agent fails.
+///
+/// [`ControlSignal`](super::signal::ControlSignal)s aren't failures, so they're returned without restoring the record.
+pub struct Transactional<A> {
+    pub(crate) agent: A,
+}
+
+impl<R: Snapshot, A: Agent<R>> Agent<R> for Transactional<A>
+where
+    A::Error: 'static,
+{
+    type Error = A::Error;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let state = record.snapshot();
+        if let Err(e) = self.agent.handle(record).await {
+            if !is_signal(&e) {
+                record.restore(state);
+            }
+            return Err(e);
+        }
+        return Ok(());
+    }
+}
+
+impl<R: Snapshot, A: AgentRef<R>> AgentRef<R> for Transactional<A>
+where
+    A::Error: 'static,
+{
+    async fn handle_ref(&self, record: &mut R) -> Result<(), Self::Error> {
+        let state = record.snapshot();
+        if let Err(e) = self.agent.handle_ref(record).await {
+            if !is_signal(&e) {
+                record.restore(state);
+            }
+            return Err(e);
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        control_flow::parallel::reduce,
+        record::{ChatRecord, Record},
+        testing::{fail, say, Failure},
+        Conversation, MainConversation,
+    };
+    use libopenai::chat::Role;
+
+    /// Agent that replaces the record with a new one, and then fails
+    struct ReplaceAndFail;
+
+    impl Agent<ChatRecord> for ReplaceAndFail {
+        type Error = Failure;
+
+        async fn handle(&mut self, record: &mut ChatRecord) -> Result<(), Self::Error> {
+            let _ = std::mem::take(record);
+            record.push(Role::User, "replaced").unwrap();
+            return Err(Failure("boom"));
+        }
+    }
+
+    fn contents(record: &ChatRecord) -> Vec<&str> {
+        return record
+            .messages()
+            .iter()
+            .map(|message| message.content.as_ref())
+            .collect();
+    }
+
+    #[tokio::test]
+    async fn restore_replaced_record() {
+        let mut record = ChatRecord::new();
+        record.push_named(Some("user"), Role::User, "a").unwrap();
+
+        let e = ReplaceAndFail.transactional().handle(&mut record).await;
+        assert_eq!(e.unwrap_err(), Failure("boom"));
+        assert_eq!(contents(&record), ["a"]);
+        assert_eq!(record.author(0), Some("user"));
+
+        // Records replaced by a custom merge strategy are restored too
+        let e = MainConversation::new()
+            .parallel(reduce(|record: &mut ChatRecord, _| {
+                *record = ChatRecord::new();
+                Ok(())
+            }))
+            .agent(say("b"))
+            .end_parallel()
+            .agent(fail("boom"))
+            .transactional()
+            .handle(&mut record)
+            .await;
+
+        assert!(e.is_err());
+        assert_eq!(contents(&record), ["a"]);
+    }
+
+    #[tokio::test]
+    async fn keep_record_on_success() {
+        let mut record = ChatRecord::new();
+        Agent::<ChatRecord>::transactional(MainConversation::new().agent(say("a")).agent(say("b")))
+            .handle(&mut record)
+            .await
+            .unwrap();
+
+        assert_eq!(contents(&record), ["a", "b"]);
+    }
+}
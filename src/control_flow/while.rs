@@ -1,18 +1,97 @@
-use crate::{agent::Agent, record::Record, Conversation, MainConversation};
+use super::signal::{intercept, Flow};
+use crate::{
+    agent::Agent,
+    error::{ConversationError, ErrorPolicy},
+    record::Record,
+    Conversation, MainConversation, Str,
+};
+use std::future::Future;
+use thiserror::Error;
 
+/// Error returned when a loop exceeds its maximum number of iterations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Loop exceeded its maximum of {max} iterations")]
+pub struct MaxIterationsReached {
+    pub max: usize,
+}
+
+/// Condition checked by a [`While`] loop, with the index of the iteration about to be played
+pub trait LoopPredicate<R> {
+    #[allow(async_fn_in_trait)]
+    async fn test(&mut self, record: &mut R, iteration: usize) -> bool;
+}
+
+impl<R, F: FnMut(&mut R, usize) -> bool> LoopPredicate<R> for F {
+    #[inline]
+    async fn test(&mut self, record: &mut R, iteration: usize) -> bool {
+        return (self)(record, iteration);
+    }
+}
+
+/// Loop predicate whose result is computed asynchronously
+pub struct AsyncPredicate<F>(pub(crate) F);
+
+impl<R, F: FnMut(&mut R, usize) -> Fut, Fut: Future<Output = bool>> LoopPredicate<R>
+    for AsyncPredicate<F>
+{
+    #[inline]
+    async fn test(&mut self, record: &mut R, iteration: usize) -> bool {
+        return (self.0)(record, iteration).await;
+    }
+}
+
+/// Plays a conversation for as long as the predicate holds.
+///
+/// Unless the loop is a do-while, the predicate is also checked before the first iteration.
 pub struct While<'a, R: 'static, F> {
     pub(crate) predicate: F,
     pub(crate) conversation: MainConversation<'a, R>,
+    pub(crate) max_iterations: Option<usize>,
+    pub(crate) label: Option<Str>,
+    pub(crate) check_first: bool,
 }
 
-impl<'a, R: Record, F: FnMut(&mut R) -> bool> Agent<R> for While<'a, R, F> {
+impl<'a, R: Record, F: LoopPredicate<R>> Agent<R> for While<'a, R, F> {
     type Error = color_eyre::Report;
+    const KIND: &'static str = "while";
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
-        while (self.predicate)(record) {
-            self.conversation.play_with(record).await?;
+        let mut iteration = 0;
+        let mut errors = Vec::new();
+
+        loop {
+            if (iteration > 0 || self.check_first) && !self.predicate.test(record, iteration).await
+            {
+                break;
+            }
+
+            // Errors collected by the previous iterations are returned alongside this one
+            if let Some(max) = self.max_iterations.filter(|max| iteration >= *max) {
+                let e = ConversationError::new(MaxIterationsReached { max }.into());
+                return Err(ConversationError::collect(std::iter::once(e).chain(errors))
+                    .unwrap()
+                    .into());
+            }
+
+            let result = self.conversation.play_with(record).await;
+            let flow = intercept(
+                result,
+                self.label.as_deref(),
+                iteration,
+                self.conversation.policy(),
+                &mut errors,
+            )?;
+
+            iteration += 1;
+            if let Flow::Break = flow {
+                break;
+            }
         }
-        return Ok(());
+
+        return match ConversationError::collect(errors) {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        };
     }
 }
 
@@ -21,19 +100,184 @@ pub struct WhileBuilder<'a, R: 'static, F, P> {
     pub(crate) child: While<'a, R, F>,
 }
 
-impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P> Conversation<'a, R>
-    for WhileBuilder<'a, R, F, P>
-{
+impl<'a, R: Record, F: 'a + LoopPredicate<R>, P> Conversation<'a, R> for WhileBuilder<'a, R, F, P> {
     fn agent<A: 'a + Agent<R>>(mut self, agent: A) -> Self {
         self.child.conversation = self.child.conversation.agent(agent);
         self
     }
 }
 
-impl<'a, R: Record, F: 'a + FnMut(&mut R) -> bool, P: Conversation<'a, R>>
-    WhileBuilder<'a, R, F, P>
-{
+impl<'a, R: Record, F: 'a + LoopPredicate<R>, P: Conversation<'a, R>> WhileBuilder<'a, R, F, P> {
+    pub(crate) fn new(parent: P, predicate: F, check_first: bool) -> Self {
+        return Self {
+            parent,
+            child: While {
+                predicate,
+                conversation: MainConversation::new(),
+                max_iterations: None,
+                label: None,
+                check_first,
+            },
+        };
+    }
+
+    /// Fails with [`MaxIterationsReached`] if the loop wants to run for more than `max` iterations
+    pub fn max_iterations(mut self, max: usize) -> Self {
+        self.child.max_iterations = Some(max);
+        self
+    }
+
+    /// Labels the loop, so that [`ControlSignal`](super::signal::ControlSignal)s can target it from within nested loops
+    pub fn label(mut self, label: impl Into<Str>) -> Self {
+        self.child.label = Some(label.into());
+        self
+    }
+
+    /// Sets what the loop's conversation does when one of it's agents fails.
+    ///
+    /// If the policy is [`Collect`](ErrorPolicy::Collect), the loop keeps going after an iteration fails,
+    /// and returns every error once it has finished.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.child.conversation = self.child.conversation.error_policy(policy);
+        self
+    }
+
     pub fn end_while(self) -> P {
         return self.parent.agent(self.child);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fail, say, Log};
+
+    #[tokio::test]
+    async fn while_checks_first() {
+        let log = MainConversation::<Log>::new()
+            .while_loop(|_, _| false)
+            .agent(say("while"))
+            .end_while()
+            .do_while(|_, _| false)
+            .agent(say("do-while"))
+            .end_while()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["do-while"]);
+    }
+
+    #[tokio::test]
+    async fn predicate_iteration() {
+        let mut iterations = Vec::new();
+        let log = MainConversation::<Log>::new()
+            .do_while(|_, i| {
+                iterations.push(i);
+                i < 3
+            })
+            .agent(say("a"))
+            .end_while()
+            .while_async(|log, i| {
+                let len = log.0.len();
+                async move { len + i < 5 }
+            })
+            .agent(say("b"))
+            .end_while()
+            .play()
+            .await
+            .unwrap();
+
+        assert_eq!(log.0, ["a", "a", "a", "b"]);
+        assert_eq!(iterations, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn max_iterations() {
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .while_loop(|_, _| true)
+            .max_iterations(2)
+            .agent(say("a"))
+            .end_while()
+            .play_with(&mut record)
+            .await
+            .unwrap_err();
+
+        assert_eq!(record.0, ["a", "a"]);
+        assert_eq!(e.path()[0].kind, "while");
+        assert_eq!(
+            e.downcast_ref::<MaxIterationsReached>(),
+            Some(&MaxIterationsReached { max: 2 })
+        );
+
+        // Loops that stop by themselves within the limit don't fail
+        let log = MainConversation::<Log>::new()
+            .while_loop(|_, i| i < 2)
+            .max_iterations(2)
+            .agent(say("a"))
+            .end_while()
+            .play()
+            .await
+            .unwrap();
+        assert_eq!(log.0, ["a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn max_iterations_collected_errors() {
+        let e = MainConversation::<Log>::new()
+            .while_loop(|_, _| true)
+            .max_iterations(2)
+            .error_policy(ErrorPolicy::Collect)
+            .agent(fail("boom"))
+            .end_while()
+            .play()
+            .await
+            .unwrap_err();
+
+        let errors = e.errors().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "Agent at `while[0]` failed: Loop exceeded its maximum of 2 iterations (and 2 more errors)",
+                "Agent at `while[0]#0/agent[0]` failed: boom",
+                "Agent at `while[0]#1/agent[0]` failed: boom",
+            ]
+        );
+        assert_eq!(
+            e.downcast_ref::<MaxIterationsReached>(),
+            Some(&MaxIterationsReached { max: 2 })
+        );
+    }
+
+    #[tokio::test]
+    async fn error_policy() {
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .while_loop(|_, i| i < 2)
+            .agent(say("a"))
+            .agent(fail("boom"))
+            .agent(say("b"))
+            .end_while()
+            .play_with(&mut record)
+            .await
+            .unwrap_err();
+
+        assert_eq!(record.0, ["a"]);
+        assert_eq!(e.to_string(), "Agent at `while[0]#0/agent[1]` failed: boom");
+
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .while_loop(|_, i| i < 2)
+            .error_policy(ErrorPolicy::SkipAndContinue)
+            .agent(say("a"))
+            .agent(fail("boom"))
+            .agent(say("b"))
+            .end_while()
+            .play_with(&mut record)
+            .await;
+
+        assert!(e.is_ok());
+        assert_eq!(record.0, ["a", "b", "a", "b"]);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{agent::DynAgent, Str};
+use std::fmt::{Debug, Display};
+
+/// Defines what a conversation does when one of it's agents fails.
+///
+/// Control signals (like [`Break`](crate::control_flow::signal::Break)) aren't affected by the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stops the conversation, returning the error
+    #[default]
+    Abort,
+    /// Logs the error, and continues with the next agent
+    SkipAndContinue,
+    /// Continues with the next agent, and returns every error once the conversation has finished
+    Collect,
+}
+
+/// Location of an agent within it's parent conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSegment {
+    /// Kind of the agent, as reported by [`Agent::KIND`](crate::agent::Agent::KIND)
+    pub kind: &'static str,
+    /// Index of the agent within it's parent conversation
+    pub index: usize,
+    /// Branch of the agent that was being played, if any
+    pub branch: Option<Str>,
+    /// Iteration of the agent that was being played, if it's a loop
+    pub iteration: Option<usize>,
+}
+
+/// Formatted as `kind[index]`, followed by `:branch` and `#iteration` when present
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]", self.kind, self.index)?;
+        if let Some(branch) = self.branch.as_deref() {
+            write!(f, ":{branch}")?;
+        }
+        if let Some(iteration) = self.iteration {
+            write!(f, "#{iteration}")?;
+        }
+        return Ok(());
+    }
+}
+
+/// Error returned when an agent of a conversation fails.
+///
+/// It reports where the failing agent is located within the conversation (for example `while[0]#3/agent[1]`),
+/// alongside the original error, which can be retrieved through [`downcast_ref`](ConversationError::downcast_ref).
+///
+/// Conversations with the [`Collect`](ErrorPolicy::Collect) policy return the first error they found,
+/// with the rest of them available through [`others`](ConversationError::others).
+#[derive(Debug)]
+pub struct ConversationError {
+    path: Vec<PathSegment>,
+    name: Option<Str>,
+    branch: Option<Str>,
+    iteration: Option<usize>,
+    source: color_eyre::Report,
+    others: Vec<ConversationError>,
+}
+
+impl ConversationError {
+    /// Path from the outermost conversation to the agent that failed
+    #[inline]
+    pub fn path(&self) -> &[PathSegment] {
+        return &self.path;
+    }
+
+    /// Name of the agent that failed, if it has one
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        return self.name.as_deref();
+    }
+
+    /// Iteration of the innermost loop that was being played when the agent failed, if any
+    pub fn iteration(&self) -> Option<usize> {
+        return self.path.iter().rev().find_map(|segment| segment.iteration);
+    }
+
+    /// Errors found after this one, when the conversation collects it's errors
+    #[inline]
+    pub fn others(&self) -> &[ConversationError] {
+        return &self.others;
+    }
+
+    /// Iterates over this error, and the ones found after it
+    pub fn errors(&self) -> impl Iterator<Item = &ConversationError> {
+        return std::iter::once(self).chain(self.others.iter());
+    }
+
+    /// Original error returned by the agent
+    #[inline]
+    pub fn report(&self) -> &color_eyre::Report {
+        return &self.source;
+    }
+
+    #[inline]
+    pub fn into_report(self) -> color_eyre::Report {
+        return self.source;
+    }
+
+    /// Attempts to downcast the original error into it's concrete type
+    #[inline]
+    pub fn downcast_ref<E: Display + Debug + Send + Sync + 'static>(&self) -> Option<&E> {
+        return self.source.downcast_ref();
+    }
+
+    /// Attempts to downcast the original error into it's concrete type
+    pub fn downcast<E: Display + Debug + Send + Sync + 'static>(self) -> Result<E, Self> {
+        return match self.source.downcast() {
+            Ok(e) => Ok(e),
+            Err(source) => Err(Self { source, ..self }),
+        };
+    }
+
+    /// Builds the error of the `index`-th agent of a conversation.
+    ///
+    /// If the agent is itself a conversation (or contains one), it's path is extended instead.
+    pub(crate) fn at<R>(agent: &DynAgent<'_, R>, index: usize, error: color_eyre::Report) -> Self {
+        return match error.downcast::<Self>() {
+            Ok(inner) => inner.prefixed(agent.kind(), index),
+            Err(source) => Self {
+                path: vec![PathSegment {
+                    kind: agent.kind(),
+                    index,
+                    branch: None,
+                    iteration: None,
+                }],
+                name: agent.name(),
+                branch: None,
+                iteration: None,
+                source,
+                others: Vec::new(),
+            },
+        };
+    }
+
+    /// Builds the error of an agent containing a conversation (like a loop exceeding it's maximum of iterations),
+    /// so that it can be combined with the errors of it's conversation.
+    ///
+    /// It's path is filled in once it reaches the parent conversation of the agent.
+    pub(crate) fn new(source: color_eyre::Report) -> Self {
+        return Self {
+            path: Vec::new(),
+            name: None,
+            branch: None,
+            iteration: None,
+            source,
+            others: Vec::new(),
+        };
+    }
+
+    /// Combines the specified errors into one, in order.
+    pub(crate) fn collect(errors: impl IntoIterator<Item = Self>) -> Option<Self> {
+        let mut errors = errors.into_iter().flat_map(|mut e| {
+            let others = std::mem::take(&mut e.others);
+            std::iter::once(e).chain(others)
+        });
+
+        let mut first = errors.next()?;
+        first.others = errors.collect();
+        return Some(first);
+    }
+
+    /// Removes the errors found after this one
+    pub(crate) fn take_others(&mut self) -> Vec<Self> {
+        return std::mem::take(&mut self.others);
+    }
+
+    /// Marks the branch that was being played by the agent containing this error's conversation
+    pub(crate) fn in_branch(mut self, branch: impl Into<Str>) -> Self {
+        let branch = branch.into();
+        self.others = self
+            .others
+            .into_iter()
+            .map(|e| e.in_branch(branch.clone()))
+            .collect();
+        self.branch = Some(branch);
+        self
+    }
+
+    /// Marks the iteration that was being played by the loop containing this error's conversation
+    pub(crate) fn in_iteration(mut self, iteration: usize) -> Self {
+        self.others = self
+            .others
+            .into_iter()
+            .map(|e| e.in_iteration(iteration))
+            .collect();
+        self.iteration = Some(iteration);
+        self
+    }
+
+    fn prefixed(mut self, kind: &'static str, index: usize) -> Self {
+        self.path.insert(
+            0,
+            PathSegment {
+                kind,
+                index,
+                branch: self.branch.take(),
+                iteration: self.iteration.take(),
+            },
+        );
+        self.others = self
+            .others
+            .into_iter()
+            .map(|e| e.prefixed(kind, index))
+            .collect();
+        self
+    }
+}
+
+impl Display for ConversationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Agent at `")?;
+        for (i, segment) in self.path.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            Display::fmt(segment, f)?;
+        }
+        f.write_str("`")?;
+
+        if let Some(name) = self.name.as_deref() {
+            write!(f, " ({name})")?;
+        }
+        write!(f, " failed: {}", self.source)?;
+
+        if !self.others.is_empty() {
+            write!(f, " (and {} more errors)", self.others.len())?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for ConversationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return Some(&*self.source);
+    }
+}
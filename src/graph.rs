@@ -0,0 +1,164 @@
+use crate::{
+    agent::{Agent, DynAgent},
+    error::ConversationError,
+    record::Record,
+    Str,
+};
+use thiserror::Error;
+
+/// Error returned when a conversation graph can't be walked
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GraphError {
+    #[error("Graph has no nodes")]
+    Empty,
+    #[error("Unknown node `{0}`")]
+    UnknownNode(Str),
+    #[error("No transition out of node `{0}` is allowed")]
+    NoTransition(Str),
+    #[error("Graph exceeded it's maximum of {0} steps")]
+    MaxSteps(usize),
+}
+
+struct Node<'a, R: 'static> {
+    name: Str,
+    agent: DynAgent<'a, R>,
+    terminal: bool,
+}
+
+struct Edge<'a, R> {
+    from: Str,
+    to: Str,
+    guard: Box<dyn 'a + FnMut(&R) -> bool>,
+}
+
+/// Conversation shaped as a state machine, where nodes are agents, and edges are the allowed transitions between them.
+///
+/// Starting from the start node, every step plays the current node, and moves to the target of the first outgoing
+/// edge whose guard holds (in the order the edges were added). The walk ends once a terminal node has been played.
+pub struct ConversationGraph<'a, R: 'static> {
+    nodes: Vec<Node<'a, R>>,
+    edges: Vec<Edge<'a, R>>,
+    start: Option<Str>,
+    max_steps: Option<usize>,
+}
+
+impl<'a, R: Record> ConversationGraph<'a, R> {
+    pub fn new() -> Self {
+        return Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            start: None,
+            max_steps: None,
+        };
+    }
+
+    /// Adds a node to the graph
+    pub fn node<A: 'a + Agent<R>>(mut self, name: impl Into<Str>, agent: A) -> Self {
+        self.nodes.push(Node {
+            name: name.into(),
+            agent: DynAgent::from_agent(agent),
+            terminal: false,
+        });
+        self
+    }
+
+    /// Adds a node to the graph, which ends the walk after being played
+    pub fn terminal<A: 'a + Agent<R>>(mut self, name: impl Into<Str>, agent: A) -> Self {
+        self.nodes.push(Node {
+            name: name.into(),
+            agent: DynAgent::from_agent(agent),
+            terminal: true,
+        });
+        self
+    }
+
+    /// Allows transitioning from one node to another whenever `guard` holds
+    pub fn edge<F: 'a + FnMut(&R) -> bool>(
+        mut self,
+        from: impl Into<Str>,
+        to: impl Into<Str>,
+        guard: F,
+    ) -> Self {
+        self.edges.push(Edge {
+            from: from.into(),
+            to: to.into(),
+            guard: Box::new(guard),
+        });
+        self
+    }
+
+    /// Allows transitioning from one node to another unconditionally
+    pub fn always(self, from: impl Into<Str>, to: impl Into<Str>) -> Self {
+        return self.edge(from, to, |_| true);
+    }
+
+    /// Sets the node the walk starts from. By default, it's the first node that was added
+    pub fn start(mut self, name: impl Into<Str>) -> Self {
+        self.start = Some(name.into());
+        self
+    }
+
+    /// Fails with [`GraphError::MaxSteps`] if the walk wants to play more than `max` nodes
+    pub fn max_steps(mut self, max: usize) -> Self {
+        self.max_steps = Some(max);
+        self
+    }
+
+    fn find(&self, name: &str) -> Result<usize, GraphError> {
+        return self
+            .nodes
+            .iter()
+            .position(|node| node.name == name)
+            .ok_or_else(|| GraphError::UnknownNode(Str::Owned(name.to_string())));
+    }
+}
+
+impl<'a, R: Record> Default for ConversationGraph<'a, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, R: Record> Agent<R> for ConversationGraph<'a, R> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "graph";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        let mut current = match self.start.as_deref() {
+            Some(start) => self.find(start)?,
+            None if self.nodes.is_empty() => return Err(GraphError::Empty.into()),
+            None => 0,
+        };
+
+        let mut step = 0;
+        loop {
+            if let Some(max) = self.max_steps.filter(|max| step >= *max) {
+                return Err(GraphError::MaxSteps(max).into());
+            }
+
+            let node = &mut self.nodes[current];
+            if let Err(e) = node.agent.handle(record).await {
+                return Err(ConversationError::at(&node.agent, current, e)
+                    .in_branch(node.name.clone())
+                    .in_iteration(step)
+                    .into());
+            }
+
+            if node.terminal {
+                return Ok(());
+            }
+
+            let name = node.name.clone();
+            let next = self
+                .edges
+                .iter_mut()
+                .find_map(|edge| {
+                    (edge.from == name && (edge.guard)(record)).then(|| edge.to.clone())
+                })
+                .ok_or(GraphError::NoTransition(name))?;
+
+            current = self.find(&next)?;
+            step += 1;
+        }
+    }
+}
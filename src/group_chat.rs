@@ -0,0 +1,166 @@
+use crate::{
+    agent::{Agent, DynAgent},
+    error::ConversationError,
+    record::Record,
+    termination::Termination,
+    Str,
+};
+use selector::SpeakerSelector;
+use thiserror::Error;
+
+pub mod selector;
+
+/// Error returned when a group chat can't continue
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GroupChatError {
+    #[error("Group chat has no participants")]
+    NoParticipants,
+    #[error("Selected speaker #{0} isn't a participant of the group chat")]
+    InvalidSpeaker(usize),
+}
+
+/// Named agent participating in a [`GroupChat`].
+///
+/// The messages it pushes without a name of their own are attributed to the participant's name.
+pub struct Participant<'a, R: 'static> {
+    name: Str,
+    description: Option<Str>,
+    agent: DynAgent<'a, R>,
+}
+
+impl<'a, R> Participant<'a, R> {
+    #[inline]
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    #[inline]
+    pub fn description(&self) -> Option<&str> {
+        return self.description.as_deref();
+    }
+}
+
+/// Conversation between a set of named participants, where the next speaker is chosen
+/// by a [`SpeakerSelector`] every round, until a termination condition is met.
+pub struct GroupChat<'a, R: 'static, S, T> {
+    participants: Vec<Participant<'a, R>>,
+    selector: S,
+    termination: T,
+    max_rounds: Option<usize>,
+}
+
+impl<'a, R: Record, S: SpeakerSelector<R>> GroupChat<'a, R, S, fn(&R, usize) -> bool> {
+    /// Creates a new group chat, which will go on until it's stopped by [`until`](GroupChat::until)
+    /// or [`max_rounds`](GroupChat::max_rounds)
+    pub fn new(selector: S) -> Self {
+        return Self {
+            participants: Vec::new(),
+            selector,
+            termination: |_, _| false,
+            max_rounds: None,
+        };
+    }
+}
+
+impl<'a, R: Record, S: SpeakerSelector<R>, T> GroupChat<'a, R, S, T> {
+    /// Adds a participant to the chat, described by the agent's own [`description`](Agent::description)
+    pub fn participant<A: 'a + Agent<R>>(mut self, name: impl Into<Str>, agent: A) -> Self {
+        self.participants.push(Participant {
+            name: name.into(),
+            description: agent
+                .description()
+                .map(|description| Str::Owned(description.to_string())),
+            agent: DynAgent::from_agent(agent),
+        });
+        self
+    }
+
+    /// Adds a participant to the chat, identified by the agent's own [`name`](Agent::name) and
+    /// [`description`](Agent::description).
+    ///
+    /// Unnamed agents are named after their position in the chat.
+    pub fn named_participant<A: 'a + Agent<R>>(self, agent: A) -> Self {
+        let name = match agent.name() {
+            Some(name) => Str::Owned(name.to_string()),
+            None => Str::Owned(format!("participant_{}", self.participants.len())),
+        };
+        return self.participant(name, agent);
+    }
+
+    /// Adds a participant to the chat, with a description of it's role
+    pub fn described_participant<A: 'a + Agent<R>>(
+        mut self,
+        name: impl Into<Str>,
+        description: impl Into<Str>,
+        agent: A,
+    ) -> Self {
+        self.participants.push(Participant {
+            name: name.into(),
+            description: Some(description.into()),
+            agent: DynAgent::from_agent(agent),
+        });
+        self
+    }
+
+    /// Ends the chat once `termination` holds.
+    ///
+    /// The termination condition receives the number of rounds played so far.
+    pub fn until<T2: Termination<R>>(self, termination: T2) -> GroupChat<'a, R, S, T2> {
+        return GroupChat {
+            participants: self.participants,
+            selector: self.selector,
+            termination,
+            max_rounds: self.max_rounds,
+        };
+    }
+
+    /// Ends the chat after `max` rounds, even if the termination condition doesn't hold
+    pub fn max_rounds(mut self, max: usize) -> Self {
+        self.max_rounds = Some(max);
+        self
+    }
+
+    #[inline]
+    pub fn participants(&self) -> &[Participant<'a, R>] {
+        return &self.participants;
+    }
+}
+
+impl<'a, R: Record, S: SpeakerSelector<R>, T: Termination<R>> Agent<R> for GroupChat<'a, R, S, T> {
+    type Error = color_eyre::Report;
+    const KIND: &'static str = "group_chat";
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        if self.participants.is_empty() {
+            return Err(GroupChatError::NoParticipants.into());
+        }
+
+        let mut round = 0;
+        while self.max_rounds.is_none_or(|max| round < max)
+            && !self.termination.should_terminate(record, round)
+        {
+            let speaker = self
+                .selector
+                .select(record, &self.participants, round)
+                .await?;
+
+            let participant = self
+                .participants
+                .get_mut(speaker)
+                .ok_or(GroupChatError::InvalidSpeaker(speaker))?;
+
+            // Messages pushed by the participant without a name of their own are attributed to it
+            let previous = record.set_speaker(Some(participant.name.clone()));
+            let result = participant.agent.handle(record).await;
+            record.set_speaker(previous);
+
+            if let Err(e) = result {
+                return Err(ConversationError::at(&participant.agent, speaker, e).into());
+            }
+
+            round += 1;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,115 @@
+use super::Participant;
+use rand::Rng;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines, Stdin};
+
+/// Strategy used by a [`GroupChat`](super::GroupChat) to pick the next speaker
+pub trait SpeakerSelector<R> {
+    /// Returns the index of the participant that will speak next
+    #[allow(async_fn_in_trait)]
+    async fn select(
+        &mut self,
+        record: &R,
+        participants: &[Participant<'_, R>],
+        round: usize,
+    ) -> color_eyre::Result<usize>;
+}
+
+/// Participants speak one after the other, in the order they were added
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoundRobin;
+
+impl<R> SpeakerSelector<R> for RoundRobin {
+    async fn select(
+        &mut self,
+        _: &R,
+        participants: &[Participant<'_, R>],
+        round: usize,
+    ) -> color_eyre::Result<usize> {
+        return Ok(round % participants.len());
+    }
+}
+
+/// The next speaker is picked at random
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Random;
+
+impl<R> SpeakerSelector<R> for Random {
+    async fn select(
+        &mut self,
+        _: &R,
+        participants: &[Participant<'_, R>],
+        _: usize,
+    ) -> color_eyre::Result<usize> {
+        return Ok(rand::thread_rng().gen_range(0..participants.len()));
+    }
+}
+
+/// The next speaker is picked by the user, through the standard input
+#[derive(Debug, Default)]
+pub struct Manual {
+    /// Lines of the standard input, kept between rounds so that no buffered input is lost
+    lines: Option<Lines<BufReader<Stdin>>>,
+}
+
+impl Manual {
+    pub fn new() -> Self {
+        return Self { lines: None };
+    }
+}
+
+impl<R> SpeakerSelector<R> for Manual {
+    async fn select(
+        &mut self,
+        _: &R,
+        participants: &[Participant<'_, R>],
+        round: usize,
+    ) -> color_eyre::Result<usize> {
+        println!("Round {round}, choose the next speaker:");
+        for (i, participant) in participants.iter().enumerate() {
+            match participant.description() {
+                Some(description) => println!("  {i}) {} - {description}", participant.name()),
+                None => println!("  {i}) {}", participant.name()),
+            }
+        }
+
+        let lines = self
+            .lines
+            .get_or_insert_with(|| BufReader::new(tokio::io::stdin()).lines());
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            let choice = match line.parse::<usize>() {
+                Ok(i) => (i < participants.len()).then_some(i),
+                Err(_) => participants.iter().position(|p| p.name() == line),
+            };
+
+            match choice {
+                Some(i) => return Ok(i),
+                None => println!("Unknown participant `{line}`, try again:"),
+            }
+        }
+
+        return Err(color_eyre::eyre::eyre!(
+            "Standard input closed before choosing a speaker"
+        ));
+    }
+}
+
+/// Uses a custom closure as speaker selector.
+///
+/// Closures can be used directly as speaker selectors, this function just helps infering the types of their arguments.
+#[inline]
+pub fn custom<R, F: FnMut(&R, &[Participant<'_, R>], usize) -> usize>(f: F) -> F {
+    return f;
+}
+
+impl<R, F: FnMut(&R, &[Participant<'_, R>], usize) -> usize> SpeakerSelector<R> for F {
+    #[inline]
+    async fn select(
+        &mut self,
+        record: &R,
+        participants: &[Participant<'_, R>],
+        round: usize,
+    ) -> color_eyre::Result<usize> {
+        return Ok((self)(record, participants, round));
+    }
+}
@@ -1,14 +1,33 @@
 #![feature(inline_const)]
 
 use agent::{Agent, DynAgent};
-use control_flow::r#while::{While, WhileBuilder};
+use control_flow::{
+    parallel::{Merge, Parallel, ParallelBuilder},
+    r#if::{If, IfBuilder},
+    r#while::{AsyncPredicate, WhileBuilder},
+    repeat::{Repeat, RepeatBuilder},
+    signal::{Break, Continue, ControlSignal},
+    switch::{Switch, SwitchBuilder},
+    timeout::{Elapsed, TimeoutError},
+};
+use error::{ConversationError, ErrorPolicy};
 use record::Record;
-use std::borrow::Cow;
+use std::{borrow::Cow, future::Future, time::Duration};
+use termination::{Termination, Until};
 
 pub mod agent;
 pub mod assistants;
 pub mod control_flow;
+pub mod error;
+pub mod graph;
+pub mod group_chat;
+pub mod model;
 pub mod record;
+pub mod termination;
+pub mod tool;
+
+#[cfg(test)]
+mod testing;
 
 pub(crate) type Str = Cow<'static, str>;
 
@@ -16,15 +35,144 @@ pub(crate) type Str = Cow<'static, str>;
 pub trait Conversation<'a, R: 'static + Record> {
     fn agent<A: 'a + Agent<R>>(self, agent: A) -> Self;
 
-    fn while_loop<F: 'a + FnMut(&mut R) -> bool>(self, predicate: F) -> WhileBuilder<'a, R, F, Self>
+    /// Plays the following agents for as long as `predicate` holds.
+    ///
+    /// The predicate receives the index of the iteration about to be played.
+    fn while_loop<F: 'a + FnMut(&mut R, usize) -> bool>(
+        self,
+        predicate: F,
+    ) -> WhileBuilder<'a, R, F, Self>
+    where
+        Self: Sized,
+    {
+        return WhileBuilder::new(self, predicate, true);
+    }
+
+    /// Plays the following agents until `termination` holds.
+    ///
+    /// The termination condition receives the number of iterations played so far.
+    fn until<T: 'a + Termination<R>>(self, termination: T) -> WhileBuilder<'a, R, Until<T>, Self>
     where
         Self: Sized,
     {
-        return WhileBuilder {
+        return WhileBuilder::new(self, Until(termination), true);
+    }
+
+    /// Plays the following agents once, and then for as long as `predicate` holds.
+    ///
+    /// The predicate receives the index of the iteration about to be played.
+    fn do_while<F: 'a + FnMut(&mut R, usize) -> bool>(
+        self,
+        predicate: F,
+    ) -> WhileBuilder<'a, R, F, Self>
+    where
+        Self: Sized,
+    {
+        return WhileBuilder::new(self, predicate, false);
+    }
+
+    /// Plays the following agents for as long as the future returned by `predicate` resolves to `true`.
+    ///
+    /// The predicate receives the index of the iteration about to be played.
+    fn while_async<F: 'a + FnMut(&mut R, usize) -> Fut, Fut: Future<Output = bool>>(
+        self,
+        predicate: F,
+    ) -> WhileBuilder<'a, R, AsyncPredicate<F>, Self>
+    where
+        Self: Sized,
+    {
+        return WhileBuilder::new(self, AsyncPredicate(predicate), true);
+    }
+
+    /// Plays the following agents once, and then for as long as the future returned by `predicate` resolves to `true`.
+    ///
+    /// The predicate receives the index of the iteration about to be played.
+    fn do_while_async<F: 'a + FnMut(&mut R, usize) -> Fut, Fut: Future<Output = bool>>(
+        self,
+        predicate: F,
+    ) -> WhileBuilder<'a, R, AsyncPredicate<F>, Self>
+    where
+        Self: Sized,
+    {
+        return WhileBuilder::new(self, AsyncPredicate(predicate), false);
+    }
+
+    /// Plays the following agents `times` times
+    fn repeat(self, times: usize) -> RepeatBuilder<'a, R, Self>
+    where
+        Self: Sized,
+    {
+        return RepeatBuilder {
             parent: self,
-            child: While {
-                predicate,
+            child: Repeat {
+                times,
                 conversation: MainConversation::new(),
+                label: None,
+            },
+        };
+    }
+
+    /// Plays the following agents only if `predicate` holds
+    fn if_then<F: 'a + FnMut(&mut R) -> bool>(self, predicate: F) -> IfBuilder<'a, R, F, Self>
+    where
+        Self: Sized,
+    {
+        return IfBuilder {
+            parent: self,
+            child: If {
+                predicate,
+                then: MainConversation::new(),
+                otherwise: MainConversation::new(),
+            },
+        };
+    }
+
+    /// Breaks out of the nearest enclosing loop if `predicate` holds
+    fn break_if<F: 'a + FnMut(&mut R) -> bool>(self, predicate: F) -> Self
+    where
+        Self: Sized,
+    {
+        return self.if_then(predicate).agent(Break::new()).end_if();
+    }
+
+    /// Skips to the next iteration of the nearest enclosing loop if `predicate` holds
+    fn continue_if<F: 'a + FnMut(&mut R) -> bool>(self, predicate: F) -> Self
+    where
+        Self: Sized,
+    {
+        return self.if_then(predicate).agent(Continue::new()).end_if();
+    }
+
+    /// Plays the following agents concurrently, each one with it's own copy of the record,
+    /// and combines their records back with `merge`
+    fn parallel<M: 'a + Merge<R>>(self, merge: M) -> ParallelBuilder<'a, R, M, Self>
+    where
+        Self: Sized,
+        R: Clone,
+    {
+        return ParallelBuilder {
+            parent: self,
+            child: Parallel {
+                agents: Vec::new(),
+                merge,
+            },
+        };
+    }
+
+    /// Plays the arm whose key matches the one returned by `selector`
+    fn switch<K: 'a + PartialEq, F: 'a + FnMut(&R) -> K>(
+        self,
+        selector: F,
+    ) -> SwitchBuilder<'a, R, F, K, Self>
+    where
+        Self: Sized,
+    {
+        return SwitchBuilder {
+            parent: self,
+            child: Switch {
+                selector,
+                cases: Vec::new(),
+                default: None,
             },
         };
     }
@@ -33,17 +181,32 @@ pub trait Conversation<'a, R: 'static + Record> {
 #[derive(Default)]
 pub struct MainConversation<'a, R: 'static> {
     agents: Vec<DynAgent<'a, R>>,
+    policy: ErrorPolicy,
 }
 
 impl<'a, R> MainConversation<'a, R> {
     /// Creates a new conversation
     pub fn new() -> Self {
-        return Self { agents: Vec::new() };
+        return Self {
+            agents: Vec::new(),
+            policy: ErrorPolicy::Abort,
+        };
+    }
+
+    /// Sets what the conversation does when one of it's agents fails
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn policy(&self) -> ErrorPolicy {
+        return self.policy;
     }
 }
 
 impl<'a, R: Record> MainConversation<'a, R> {
-    pub async fn play(&mut self) -> color_eyre::Result<R>
+    pub async fn play(&mut self) -> Result<R, ConversationError>
     where
         R: Default,
     {
@@ -52,11 +215,43 @@ impl<'a, R: Record> MainConversation<'a, R> {
         return Ok(record);
     }
 
-    pub async fn play_with(&mut self, record: &mut R) -> color_eyre::Result<()> {
-        for agent in self.agents.iter_mut() {
-            agent.handle(record).await?
+    pub async fn play_with(&mut self, record: &mut R) -> Result<(), ConversationError> {
+        let mut errors = Vec::new();
+
+        for (i, agent) in self.agents.iter_mut().enumerate() {
+            let Err(e) = agent.handle(record).await else {
+                continue;
+            };
+
+            let e = ConversationError::at(agent, i, e);
+            if e.downcast_ref::<ControlSignal>().is_some() {
+                errors.insert(0, e);
+                break;
+            }
+
+            match self.policy {
+                ErrorPolicy::Abort => return Err(e),
+                ErrorPolicy::SkipAndContinue => log::warn!("{e}"),
+                ErrorPolicy::Collect => errors.push(e),
+            }
         }
-        return Ok(());
+
+        return match ConversationError::collect(errors) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        };
+    }
+
+    /// Plays the conversation, cancelling it and failing with [`TimeoutError::Elapsed`] if it doesn't finish within `duration`
+    pub async fn play_with_deadline(
+        &mut self,
+        record: &mut R,
+        duration: Duration,
+    ) -> Result<(), TimeoutError<ConversationError>> {
+        return match tokio::time::timeout(duration, self.play_with(record)).await {
+            Ok(result) => result.map_err(TimeoutError::Agent),
+            Err(_) => Err(TimeoutError::Elapsed(Elapsed(duration))),
+        };
     }
 }
 
@@ -68,9 +263,88 @@ impl<'a, R: Record> Conversation<'a, R> for MainConversation<'a, R> {
 }
 
 impl<'a, R: Record> Agent<R> for MainConversation<'a, R> {
-    type Error = color_eyre::Report;
+    type Error = ConversationError;
+    const KIND: &'static str = "conversation";
 
     async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
         self.play_with(record).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fail, say, Delayed, Failure, Log};
+
+    #[tokio::test]
+    async fn nested_conversation_path() {
+        let e = MainConversation::<Log>::new()
+            .agent(say("a"))
+            .agent(
+                MainConversation::new()
+                    .agent(say("b"))
+                    .agent(Agent::<Log>::named(fail("boom"), "failing")),
+            )
+            .agent(say("unreachable"))
+            .play()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            e.to_string(),
+            "Agent at `conversation[1]/agent[1]` (failing) failed: boom"
+        );
+        assert_eq!(e.name(), Some("failing"));
+        assert_eq!(e.iteration(), None);
+        assert_eq!(e.downcast::<Failure>().unwrap(), Failure("boom"));
+    }
+
+    #[tokio::test]
+    async fn collect_errors() {
+        let mut record = Log::default();
+        let e = MainConversation::new()
+            .error_policy(ErrorPolicy::Collect)
+            .agent(fail("first"))
+            .agent(say("a"))
+            .agent(fail("second"))
+            .play_with(&mut record)
+            .await
+            .unwrap_err();
+
+        assert_eq!(record.0, ["a"]);
+        assert_eq!(
+            e.to_string(),
+            "Agent at `agent[0]` failed: first (and 1 more errors)"
+        );
+        assert_eq!(
+            e.others()[0].to_string(),
+            "Agent at `agent[2]` failed: second"
+        );
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        let mut record = Log::default();
+        let result = MainConversation::new()
+            .agent(say("a"))
+            .agent(Delayed {
+                millis: 500,
+                content: "b",
+            })
+            .play_with_deadline(&mut record, Duration::from_millis(20))
+            .await;
+
+        assert!(matches!(result, Err(TimeoutError::Elapsed(_))));
+        assert_eq!(record.0, ["a"]);
+
+        let result = MainConversation::<Log>::new()
+            .agent(fail("boom"))
+            .play_with_deadline(&mut record, Duration::from_millis(500))
+            .await;
+
+        let Err(TimeoutError::Agent(e)) = result else {
+            panic!("expected the conversation's error, got {result:?}");
+        };
+        assert_eq!(e.downcast_ref::<Failure>(), Some(&Failure("boom")));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    tool::{Tool, ToolCall},
+    Str,
+};
+use libopenai::chat::{Message, Role};
+use serde_json::Value;
+use std::{fmt::Debug, sync::Arc};
+use thiserror::Error;
+
+pub mod llama_cpp;
+pub mod ollama;
+pub mod openai;
+pub mod openai_compatible;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+pub(crate) mod scripted;
+
+static DEFAULT_PARAMS: ChatParams = ChatParams {
+    temperature: None,
+    top_p: None,
+    max_tokens: None,
+    stop: Vec::new(),
+    seed: None,
+};
+
+/// Error returned by the backends that talk to a model server through HTTP
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No response choices found")]
+    NoChoices,
+    /// Error reported by the server
+    #[error("Server error ({status}): {message}")]
+    Server {
+        status: reqwest::StatusCode,
+        message: String,
+    },
+    /// Feature of the request that the backend can't handle
+    #[error("{0} aren't supported by this backend")]
+    Unsupported(&'static str),
+}
+
+/// Sends the request, turning the error reported by the server (if any) into a [`ModelError`]
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, ModelError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    // Servers report errors either as `{ "error": "..." }` or `{ "error": { "message": "..." } }`
+    let body = response.text().await?;
+    let message = match serde_json::from_str::<Value>(&body) {
+        Ok(error) => match &error["error"] {
+            Value::String(message) => message.clone(),
+            error => error["message"].as_str().unwrap_or(&body).to_string(),
+        },
+        Err(_) => body,
+    };
+
+    return Err(ModelError::Server { status, message });
+}
+
+/// Message of a chat, as sent to (or received from) a [`ChatModel`]
+#[derive(Debug, Clone)]
+pub struct ModelMessage {
+    pub role: Role,
+    pub content: Str,
+    /// Tools the model asked to call alongside the message
+    pub tool_calls: Vec<ToolCall>,
+    /// Identifier of the tool call whose result is the message, if it's one
+    pub tool_call_id: Option<Str>,
+}
+
+impl ModelMessage {
+    pub fn new(role: Role, content: impl Into<Str>) -> Self {
+        return Self {
+            role,
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        };
+    }
+}
+
+impl From<Message<'static>> for ModelMessage {
+    #[inline]
+    fn from(message: Message<'static>) -> Self {
+        return Self::new(message.role, message.content);
+    }
+}
+
+/// Sampling parameters of a completion. Unset parameters are left to the backend's defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Maximum number of tokens the reply can have
+    pub max_tokens: Option<u32>,
+    /// Sequences that stop the generation of the reply
+    pub stop: Vec<Str>,
+    pub seed: Option<u64>,
+}
+
+/// Request sent to a [`ChatModel`]
+#[derive(Debug, Clone, Copy)]
+pub struct ChatRequest<'a> {
+    /// Name of the model that will complete the chat
+    pub model: &'a str,
+    pub messages: &'a [ModelMessage],
+    /// Tools the model can ask to call
+    pub tools: &'a [Tool],
+    pub params: &'a ChatParams,
+}
+
+impl<'a> ChatRequest<'a> {
+    /// Creates a new request, without tools and with the default parameters
+    pub fn new(model: &'a str, messages: &'a [ModelMessage]) -> Self {
+        return Self {
+            model,
+            messages,
+            tools: &[],
+            params: &DEFAULT_PARAMS,
+        };
+    }
+
+    pub fn tools(mut self, tools: &'a [Tool]) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn params(mut self, params: &'a ChatParams) -> Self {
+        self.params = params;
+        self
+    }
+}
+
+/// Callback that receives the tokens of a reply, as they're streamed
+#[derive(Clone)]
+pub struct TokenCallback(Arc<dyn Send + Sync + Fn(&str)>);
+
+impl TokenCallback {
+    pub fn new<F: 'static + Send + Sync + Fn(&str)>(f: F) -> Self {
+        return Self(Arc::new(f));
+    }
+
+    #[inline]
+    pub fn call(&self, token: &str) {
+        (self.0)(token)
+    }
+}
+
+impl Debug for TokenCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TokenCallback").finish_non_exhaustive()
+    }
+}
+
+/// Backend able to complete chats, like OpenAI's API or a local model server
+pub trait ChatModel {
+    type Error: 'static + Send + Sync + std::error::Error;
+
+    /// Completes the chat, returning the model's reply
+    #[allow(async_fn_in_trait)]
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error>;
+
+    /// Completes the chat, calling `on_token` with every token of the reply as it's generated.
+    ///
+    /// Backends that can't stream their replies call it once, with the whole reply.
+    #[allow(async_fn_in_trait)]
+    async fn complete_streaming(
+        &self,
+        request: ChatRequest<'_>,
+        on_token: &TokenCallback,
+    ) -> Result<ModelMessage, Self::Error> {
+        let reply = self.complete(request).await?;
+        on_token.call(&reply.content);
+        return Ok(reply);
+    }
+}
@@ -0,0 +1,425 @@
+use super::{send, ChatModel, ChatParams, ChatRequest, ModelError, ModelMessage, TokenCallback};
+use crate::Str;
+use futures::StreamExt;
+use libopenai::chat::Role;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const LLAMA_CPP_BASE_URL: &str = "http://localhost:8080";
+
+/// [`ChatModel`] backed by the native API of llama.cpp's server (`llama-server`).
+///
+/// Chats are rendered with the model's own template (through `/apply-template`), and completed through `/completion`,
+/// which accepts every native sampling option of the server. Since that endpoint can't call tools, requests with tools fail
+/// with [`ModelError::Unsupported`] (the server's OpenAI-compatible API can be used for them instead, through
+/// [`OpenAiCompatible`](super::openai_compatible::OpenAiCompatible)).
+///
+/// The server only serves the model it was started with, so the model of the requests is ignored.
+#[derive(Debug, Clone)]
+pub struct LlamaCpp {
+    pub client: reqwest::Client,
+    /// Base URL of the server
+    pub base_url: Str,
+    /// Native options of the server (like `top_k`, `min_p`, `repeat_penalty` or `grammar`), which take precedence over [`ChatParams`]
+    pub options: Map<String, Value>,
+}
+
+/// Properties of a llama.cpp server, as reported by it's `/props` endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LlamaCppProps {
+    /// Path of the model the server was started with
+    #[serde(default)]
+    pub model_path: String,
+    /// Template used to render the chats sent to the model
+    #[serde(default)]
+    pub chat_template: String,
+    /// Number of requests the server can process at once
+    #[serde(default)]
+    pub total_slots: usize,
+    /// Native options used by the server when a request doesn't set them (like `n_ctx`, `temperature` or `top_k`)
+    #[serde(default)]
+    pub default_generation_settings: Map<String, Value>,
+}
+
+impl LlamaCpp {
+    pub fn new() -> Self {
+        return Self {
+            client: reqwest::Client::new(),
+            base_url: Str::Borrowed(LLAMA_CPP_BASE_URL),
+            options: Map::new(),
+        };
+    }
+
+    /// Sets the base URL of the server
+    pub fn with_base_url(mut self, base_url: impl Into<Str>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets a native option of the server (like `top_k`, `min_p`, `repeat_penalty` or `grammar`)
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Checks that the server is ready to complete chats, failing with the error it reports otherwise
+    /// (like while it's still loading the model)
+    pub async fn health(&self) -> Result<(), ModelError> {
+        send(self.client.get(format!("{}/health", self.base_url))).await?;
+        return Ok(());
+    }
+
+    /// Properties of the server, like the model it serves and it's default options
+    pub async fn props(&self) -> Result<LlamaCppProps, ModelError> {
+        let response = send(self.client.get(format!("{}/props", self.base_url))).await?;
+        return Ok(response.json::<LlamaCppProps>().await?);
+    }
+
+    /// Lists the identifiers of the models served by the server
+    pub async fn models(&self) -> Result<Vec<String>, ModelError> {
+        #[derive(Deserialize)]
+        struct Models {
+            data: Vec<Model>,
+        }
+
+        #[derive(Deserialize)]
+        struct Model {
+            id: String,
+        }
+
+        let response = send(self.client.get(format!("{}/v1/models", self.base_url))).await?;
+        let models = response.json::<Models>().await?;
+        return Ok(models.data.into_iter().map(|model| model.id).collect());
+    }
+
+    /// Sends the request to `/completion`, once it's messages have been rendered with the model's template
+    async fn send_completion(
+        &self,
+        request: ChatRequest<'_>,
+        stream: bool,
+    ) -> Result<reqwest::Response, ModelError> {
+        if !request.tools.is_empty() {
+            return Err(ModelError::Unsupported("Tool calls"));
+        }
+
+        #[derive(Deserialize)]
+        struct Template {
+            prompt: String,
+        }
+
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| serde_json::json!({ "role": message.role, "content": message.content }))
+            .collect::<Vec<_>>();
+
+        let response = send(
+            self.client
+                .post(format!("{}/apply-template", self.base_url))
+                .json(&serde_json::json!({ "messages": messages })),
+        )
+        .await?;
+        let prompt = response.json::<Template>().await?.prompt;
+
+        return send(
+            self.client
+                .post(format!("{}/completion", self.base_url))
+                .json(&self.body(prompt, request.params, stream)),
+        )
+        .await;
+    }
+
+    /// Body of a completion request, in the format of llama.cpp's native API
+    fn body(&self, prompt: String, params: &ChatParams, stream: bool) -> Value {
+        let mut body = Map::new();
+        body.insert("prompt".into(), Value::from(prompt));
+        body.insert("stream".into(), Value::from(stream));
+
+        if let Some(temperature) = params.temperature {
+            body.insert("temperature".into(), Value::from(temperature));
+        }
+        if let Some(top_p) = params.top_p {
+            body.insert("top_p".into(), Value::from(top_p));
+        }
+        if let Some(max_tokens) = params.max_tokens {
+            body.insert("n_predict".into(), Value::from(max_tokens));
+        }
+        if !params.stop.is_empty() {
+            let stop = params.stop.iter().map(|stop| Value::from(&**stop));
+            body.insert("stop".into(), stop.collect());
+        }
+        if let Some(seed) = params.seed {
+            body.insert("seed".into(), Value::from(seed));
+        }
+        body.extend(self.options.clone());
+
+        return Value::Object(body);
+    }
+}
+
+impl Default for LlamaCpp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatModel for LlamaCpp {
+    type Error = ModelError;
+
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error> {
+        let response = self.send_completion(request, false).await?;
+        let chunk = response.json::<CompletionChunk>().await?.into_result()?;
+        return Ok(ModelMessage::new(Role::Assistant, chunk.content));
+    }
+
+    /// Consumes the server-sent events of a streamed completion, building up the reply from them
+    async fn complete_streaming(
+        &self,
+        request: ChatRequest<'_>,
+        on_token: &TokenCallback,
+    ) -> Result<ModelMessage, Self::Error> {
+        let response = self.send_completion(request, true).await?;
+
+        let mut stream = std::pin::pin!(response.bytes_stream());
+        let mut buffer = Vec::new();
+        let mut content = String::new();
+
+        'stream: while let Some(bytes) = stream.next().await {
+            buffer.extend_from_slice(&bytes?);
+
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line = buffer.drain(..=end).collect::<Vec<_>>();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:") else {
+                    continue;
+                };
+
+                let chunk = serde_json::from_str::<CompletionChunk>(data.trim())?.into_result()?;
+                if !chunk.content.is_empty() {
+                    on_token.call(&chunk.content);
+                    content.push_str(&chunk.content);
+                }
+
+                // The server doesn't end the stream with `[DONE]`, but with a chunk that has `stop` set
+                if chunk.stop {
+                    break 'stream;
+                }
+            }
+        }
+
+        return Ok(ModelMessage::new(Role::Assistant, content));
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionChunk {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    stop: bool,
+    /// Error found once the stream has started, either as `"..."` or as `{ "message": "..." }`
+    #[serde(default)]
+    error: Option<Value>,
+}
+
+impl CompletionChunk {
+    fn into_result(self) -> Result<Self, ModelError> {
+        let Some(error) = &self.error else {
+            return Ok(self);
+        };
+
+        let message = match error {
+            Value::String(message) => message.clone(),
+            error => error["message"]
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string),
+        };
+        return Err(ModelError::Server {
+            status: reqwest::StatusCode::OK,
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::mock::{serve, Response},
+        tool::ToolRegistry,
+    };
+    use std::sync::{Arc, Mutex};
+
+    const TEMPLATE: &str = r#"{"prompt":"<|user|>\nHi<|end|>\n<|assistant|>\n"}"#;
+
+    #[tokio::test]
+    async fn complete() {
+        let (url, server) = serve(vec![
+            Response::ok([TEMPLATE]),
+            Response::ok([
+                r#"{"content":"Hello!","stop":true,"tokens_predicted":3,"stop_type":"eos"}"#,
+            ]),
+        ])
+        .await;
+
+        let backend = LlamaCpp::new()
+            .with_base_url(url)
+            .option("top_k", 40)
+            .option("temperature", 0.2);
+
+        let messages = [
+            ModelMessage::new(Role::System, "Be nice"),
+            ModelMessage::new(Role::User, "Hi"),
+        ];
+        let params = ChatParams {
+            temperature: Some(1.0),
+            max_tokens: Some(16),
+            stop: vec![Str::Borrowed("<|end|>")],
+            ..Default::default()
+        };
+        let reply = backend
+            .complete(ChatRequest::new("phi3", &messages).params(&params))
+            .await
+            .unwrap();
+        assert!(matches!(reply.role, Role::Assistant));
+        assert_eq!(reply.content, "Hello!");
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].line, "POST /apply-template HTTP/1.1");
+        let template = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(template["messages"][0]["role"], "system");
+        assert_eq!(template["messages"][1]["content"], "Hi");
+
+        assert_eq!(requests[1].line, "POST /completion HTTP/1.1");
+        let body = serde_json::from_str::<Value>(&requests[1].body).unwrap();
+        assert_eq!(body["prompt"], "<|user|>\nHi<|end|>\n<|assistant|>\n");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["top_k"], 40);
+        assert_eq!(body["temperature"], 0.2);
+        assert_eq!(body["n_predict"], 16);
+        assert_eq!(body["stop"], serde_json::json!(["<|end|>"]));
+    }
+
+    async fn stream(response: Response) -> (Result<ModelMessage, ModelError>, Vec<String>) {
+        let (url, server) = serve(vec![Response::ok([TEMPLATE]), response]).await;
+        let backend = LlamaCpp::new().with_base_url(url);
+        let messages = [ModelMessage::new(Role::User, "Hi")];
+
+        let tokens = Arc::new(Mutex::new(Vec::new()));
+        let on_token = TokenCallback::new({
+            let tokens = tokens.clone();
+            move |token| tokens.lock().unwrap().push(token.to_string())
+        });
+
+        let result = backend
+            .complete_streaming(ChatRequest::new("phi3", &messages), &on_token)
+            .await;
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[1].line, "POST /completion HTTP/1.1");
+        assert!(requests[1].body.contains(r#""stream":true"#));
+
+        let tokens = tokens.lock().unwrap().clone();
+        return (result, tokens);
+    }
+
+    #[tokio::test]
+    async fn stream_split_across_chunks() {
+        let (reply, tokens) = stream(Response::ok([
+            "data: {\"content\":\"Hel\",\"stop\":false}\n\ndata: {\"cont",
+            "ent\":\"lo\",\"stop\":false}\r\n\r\n",
+            "data: {\"content\":\"\",\"stop\":true,\"stop_type\":\"eos\"}\n\n",
+        ]))
+        .await;
+
+        assert_eq!(reply.unwrap().content, "Hello");
+        assert_eq!(tokens, ["Hel", "lo"]);
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let (result, tokens) = stream(Response::ok([
+            "data: {\"content\":\"Hi\",\"stop\":false}\n\n",
+            "data: {\"error\":{\"code\":500,\"message\":\"Failed to decode the batch\",\"type\":\"server_error\"}}\n\n",
+        ]))
+        .await;
+        match result {
+            Err(ModelError::Server { message, .. }) => {
+                assert_eq!(message, "Failed to decode the batch");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(tokens, ["Hi"]);
+
+        // Tools can't be called through the native API, so no request is sent
+        let tools = ToolRegistry::new().register(
+            "add",
+            "Adds two numbers",
+            serde_json::json!({ "type": "object" }),
+            |_: Value| async { Ok::<_, std::convert::Infallible>(5) },
+        );
+        let messages = [ModelMessage::new(Role::User, "2 + 3?")];
+        let result = LlamaCpp::new()
+            .with_base_url("http://127.0.0.1:1")
+            .complete(ChatRequest::new("phi3", &messages).tools(tools.tools()))
+            .await;
+        assert!(matches!(result, Err(ModelError::Unsupported(_))));
+    }
+
+    #[tokio::test]
+    async fn health() {
+        let (url, server) = serve(vec![
+            Response::status(
+                503,
+                r#"{"error":{"code":503,"message":"Loading model","type":"unavailable_error"}}"#,
+            ),
+            Response::ok([r#"{"status":"ok"}"#]),
+        ])
+        .await;
+
+        let backend = LlamaCpp::new().with_base_url(url);
+        match backend.health().await {
+            Err(ModelError::Server { status, message }) => {
+                assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(message, "Loading model");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        backend.health().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].line, "GET /health HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn props() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"default_generation_settings":{"n_ctx":4096,"temperature":0.8,"top_k":40},"total_slots":4,"model_path":"models/phi3-mini-q4.gguf","chat_template":"{% for message in messages %}...{% endfor %}","build_info":"b4000"}"#,
+        ])])
+        .await;
+
+        let props = LlamaCpp::new().with_base_url(url).props().await.unwrap();
+        assert_eq!(props.model_path, "models/phi3-mini-q4.gguf");
+        assert_eq!(props.total_slots, 4);
+        assert_eq!(props.default_generation_settings["n_ctx"], 4096);
+        assert!(props.chat_template.starts_with("{% for message"));
+        assert_eq!(server.await.unwrap()[0].line, "GET /props HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn models() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"object":"list","data":[{"id":"models/phi3-mini-q4.gguf","object":"model","owned_by":"llamacpp"}]}"#,
+        ])])
+        .await;
+
+        let models = LlamaCpp::new().with_base_url(url).models().await.unwrap();
+        assert_eq!(models, ["models/phi3-mini-q4.gguf"]);
+        assert_eq!(server.await.unwrap()[0].line, "GET /v1/models HTTP/1.1");
+    }
+}
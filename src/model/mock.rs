@@ -0,0 +1,109 @@
+//! Local HTTP server answering with canned responses, used to test the backends
+
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// Request received by the server
+#[derive(Debug)]
+pub(crate) struct Request {
+    /// Request line, like `POST /chat/completions HTTP/1.1`
+    pub line: String,
+    pub body: String,
+}
+
+/// Response sent by the server, whose body is written in the specified chunks
+#[derive(Debug)]
+pub(crate) struct Response {
+    pub status: u16,
+    pub chunks: Vec<&'static str>,
+}
+
+impl Response {
+    pub fn ok(chunks: impl IntoIterator<Item = &'static str>) -> Self {
+        return Self {
+            status: 200,
+            chunks: chunks.into_iter().collect(),
+        };
+    }
+
+    pub fn status(status: u16, body: &'static str) -> Self {
+        return Self {
+            status,
+            chunks: vec![body],
+        };
+    }
+}
+
+/// Starts a server that answers every connection with the next response, returning it's base URL.
+///
+/// The handle resolves to the requests it received, once every response has been sent.
+pub(crate) async fn serve(responses: Vec<Response>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            requests.push(read_request(&mut socket).await);
+
+            let head = format!(
+                "HTTP/1.1 {} Mock\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n",
+                response.status
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+
+            // Every chunk is flushed on it's own, so that the client receives them separately.
+            // The client may hang up before reading all of them (like after a stream's `[DONE]`).
+            for chunk in response.chunks {
+                let chunk = format!("{:x}\r\n{chunk}\r\n", chunk.len());
+                if socket.write_all(chunk.as_bytes()).await.is_err()
+                    || socket.flush().await.is_err()
+                {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            let _ = socket.write_all(b"0\r\n\r\n").await;
+        }
+        return requests;
+    });
+
+    return (url, handle);
+}
+
+async fn read_request(socket: &mut TcpStream) -> Request {
+    let mut buffer = Vec::new();
+    let mut bytes = [0; 1024];
+
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let read = socket.read(&mut bytes).await.unwrap();
+        assert!(read > 0, "connection closed before the end of the request");
+        buffer.extend_from_slice(&bytes[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map_or(0, |(_, value)| value.trim().parse::<usize>().unwrap());
+
+    while buffer.len() < head_end + length {
+        let read = socket.read(&mut bytes).await.unwrap();
+        assert!(read > 0, "connection closed before the end of the request");
+        buffer.extend_from_slice(&bytes[..read]);
+    }
+
+    return Request {
+        line: head.lines().next().unwrap_or_default().to_string(),
+        body: String::from_utf8_lossy(&buffer[head_end..head_end + length]).to_string(),
+    };
+}
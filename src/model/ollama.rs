@@ -0,0 +1,497 @@
+use super::{send, ChatModel, ChatRequest, ModelError, ModelMessage, TokenCallback};
+use crate::{
+    tool::{FunctionCall, Tool, ToolCall},
+    Str,
+};
+use futures::StreamExt;
+use libopenai::chat::Role;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// [`ChatModel`] backed by Ollama's native API
+#[derive(Debug, Clone)]
+pub struct Ollama {
+    pub client: reqwest::Client,
+    /// Base URL of the server
+    pub base_url: Str,
+    /// Native options of the model (like `num_ctx` or `repeat_penalty`), which take precedence over [`ChatParams`](super::ChatParams)
+    pub options: Map<String, Value>,
+    /// How long the model stays loaded after a request (like `"5m"`), left to the server's default when unset
+    pub keep_alive: Option<Str>,
+}
+
+/// Model available on an Ollama server
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub modified_at: String,
+    /// Size of the model, in bytes
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub digest: String,
+    #[serde(default)]
+    pub details: OllamaModelDetails,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub format: String,
+    #[serde(default)]
+    pub family: String,
+    #[serde(default)]
+    pub parameter_size: String,
+    #[serde(default)]
+    pub quantization_level: String,
+}
+
+impl Ollama {
+    pub fn new() -> Self {
+        return Self {
+            client: reqwest::Client::new(),
+            base_url: Str::Borrowed(OLLAMA_BASE_URL),
+            options: Map::new(),
+            keep_alive: None,
+        };
+    }
+
+    /// Sets the base URL of the server
+    pub fn with_base_url(mut self, base_url: impl Into<Str>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets a native option of the model (like `num_ctx` or `repeat_penalty`)
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets how long the model stays loaded after a request (like `"5m"`)
+    pub fn keep_alive(mut self, keep_alive: impl Into<Str>) -> Self {
+        self.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    /// Lists the models available on the server
+    pub async fn models(&self) -> Result<Vec<OllamaModel>, ModelError> {
+        #[derive(Deserialize)]
+        struct Tags {
+            models: Vec<OllamaModel>,
+        }
+
+        let response = send(self.client.get(format!("{}/api/tags", self.base_url))).await?;
+        return Ok(response.json::<Tags>().await?.models);
+    }
+
+    /// Body of a chat request, in the format of Ollama's API
+    fn body(&self, request: ChatRequest<'_>, stream: bool) -> Value {
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| {
+                if message.tool_call_id.is_some() {
+                    return serde_json::json!({ "role": "tool", "content": message.content });
+                }
+                if message.tool_calls.is_empty() {
+                    return serde_json::json!({ "role": message.role, "content": message.content });
+                }
+
+                // Ollama expects the arguments of a tool call as an object, instead of an encoded one
+                let tool_calls = message
+                    .tool_calls
+                    .iter()
+                    .map(|call| {
+                        serde_json::json!({
+                            "function": {
+                                "name": call.function.name,
+                                "arguments": serde_json::from_str::<Value>(&call.function.arguments)
+                                    .unwrap_or(Value::String(call.function.arguments.clone())),
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                serde_json::json!({
+                    "role": "assistant",
+                    "content": message.content,
+                    "tool_calls": tool_calls,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let params = request.params;
+        let mut options = Map::new();
+        if let Some(temperature) = params.temperature {
+            options.insert("temperature".into(), Value::from(temperature));
+        }
+        if let Some(top_p) = params.top_p {
+            options.insert("top_p".into(), Value::from(top_p));
+        }
+        if let Some(max_tokens) = params.max_tokens {
+            options.insert("num_predict".into(), Value::from(max_tokens));
+        }
+        if !params.stop.is_empty() {
+            let stop = params.stop.iter().map(|stop| Value::from(&**stop));
+            options.insert("stop".into(), stop.collect());
+        }
+        if let Some(seed) = params.seed {
+            options.insert("seed".into(), Value::from(seed));
+        }
+        options.extend(self.options.clone());
+
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": stream,
+            "options": options,
+        });
+
+        if !request.tools.is_empty() {
+            body["tools"] = request.tools.iter().map(Tool::definition).collect();
+        }
+        if let Some(keep_alive) = self.keep_alive.as_deref() {
+            body["keep_alive"] = Value::from(keep_alive);
+        }
+
+        return body;
+    }
+}
+
+impl Default for Ollama {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatModel for Ollama {
+    type Error = ModelError;
+
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/api/chat", self.base_url))
+                .json(&self.body(request, false)),
+        )
+        .await?;
+
+        let chunk = response.json::<ChatChunk>().await?;
+        if let Some(message) = chunk.error {
+            return Err(ModelError::Server {
+                status: reqwest::StatusCode::OK,
+                message,
+            });
+        }
+
+        let message = chunk.message.unwrap_or_default();
+        return Ok(ModelMessage {
+            role: Role::Assistant,
+            content: Str::Owned(message.content),
+            tool_calls: tool_calls(message.tool_calls, 0),
+            tool_call_id: None,
+        });
+    }
+
+    /// Consumes the newline-delimited JSON objects of a streamed chat, building up the reply from them
+    async fn complete_streaming(
+        &self,
+        request: ChatRequest<'_>,
+        on_token: &TokenCallback,
+    ) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/api/chat", self.base_url))
+                .json(&self.body(request, true)),
+        )
+        .await?;
+
+        let mut stream = std::pin::pin!(response.bytes_stream());
+        let mut buffer = Vec::new();
+        let mut content = String::new();
+        let mut calls = Vec::<ToolCall>::new();
+        let mut finished = false;
+
+        'stream: while !finished {
+            match stream.next().await {
+                Some(bytes) => buffer.extend_from_slice(&bytes?),
+                // The last object isn't necessarily followed by a newline
+                None => {
+                    finished = true;
+                    buffer.push(b'\n');
+                }
+            }
+
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line = buffer.drain(..=end).collect::<Vec<_>>();
+                let line = String::from_utf8_lossy(&line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let chunk = serde_json::from_str::<ChatChunk>(line.trim())?;
+                if let Some(message) = chunk.error {
+                    return Err(ModelError::Server {
+                        status: reqwest::StatusCode::OK,
+                        message,
+                    });
+                }
+
+                if let Some(message) = chunk.message {
+                    if !message.content.is_empty() {
+                        on_token.call(&message.content);
+                        content.push_str(&message.content);
+                    }
+                    calls.extend(tool_calls(message.tool_calls, calls.len()));
+                }
+
+                if chunk.done {
+                    break 'stream;
+                }
+            }
+        }
+
+        return Ok(ModelMessage {
+            role: Role::Assistant,
+            content: Str::Owned(content),
+            tool_calls: calls,
+            tool_call_id: None,
+        });
+    }
+}
+
+/// Converts the tool calls of a reply, identifying them by their position (since Ollama doesn't)
+fn tool_calls(calls: Option<Vec<OllamaToolCall>>, offset: usize) -> Vec<ToolCall> {
+    return calls
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(i, call)| ToolCall {
+            id: format!("call_{}", offset + i),
+            kind: String::from("function"),
+            function: FunctionCall {
+                name: call.function.name,
+                arguments: call.function.arguments.to_string(),
+            },
+        })
+        .collect();
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{
+            mock::{serve, Response},
+            ChatParams,
+        },
+        tool::ToolRegistry,
+    };
+    use std::sync::{Arc, Mutex};
+
+    async fn stream(response: Response) -> (Result<ModelMessage, ModelError>, Vec<String>) {
+        let (url, server) = serve(vec![response]).await;
+        let backend = Ollama::new().with_base_url(url);
+        let messages = [ModelMessage::new(Role::User, "Hi")];
+
+        let tokens = Arc::new(Mutex::new(Vec::new()));
+        let on_token = TokenCallback::new({
+            let tokens = tokens.clone();
+            move |token| tokens.lock().unwrap().push(token.to_string())
+        });
+
+        let result = backend
+            .complete_streaming(ChatRequest::new("llama3", &messages), &on_token)
+            .await;
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].line, "POST /api/chat HTTP/1.1");
+        assert!(requests[0].body.contains(r#""stream":true"#));
+
+        let tokens = tokens.lock().unwrap().clone();
+        return (result, tokens);
+    }
+
+    #[tokio::test]
+    async fn stream_split_across_chunks() {
+        let (reply, tokens) = stream(Response::ok([
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n{\"message\":{\"role\":\"ass",
+            "istant\",\"content\":\"lo\"},\"done\":false}\r\n",
+            "\n{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\"}\n",
+        ]))
+        .await;
+
+        assert_eq!(reply.unwrap().content, "Hello");
+        assert_eq!(tokens, ["Hel", "lo"]);
+    }
+
+    #[tokio::test]
+    async fn stream_without_trailing_newline() {
+        let (reply, tokens) = stream(Response::ok([
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"!\"},\"done\":true}",
+        ]))
+        .await;
+
+        assert_eq!(reply.unwrap().content, "Hi!");
+        assert_eq!(tokens, ["Hi", "!"]);
+    }
+
+    #[tokio::test]
+    async fn stream_tool_calls() {
+        let (reply, _) = stream(Response::ok([
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"add\",\"arguments\":{\"a\":2,\"b\":3}}}]},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"neg\",\"arguments\":{\"a\":1}}}]},\"done\":true}\n",
+        ]))
+        .await;
+
+        let reply = reply.unwrap();
+        assert_eq!(reply.tool_calls.len(), 2);
+        assert_eq!(reply.tool_calls[0].id, "call_0");
+        assert_eq!(reply.tool_calls[0].function.arguments, r#"{"a":2,"b":3}"#);
+        assert_eq!(reply.tool_calls[1].id, "call_1");
+        assert_eq!(reply.tool_calls[1].function.name, "neg");
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let (result, _) = stream(Response::status(
+            404,
+            r#"{"error":"model \"llama3\" not found, try pulling it first"}"#,
+        ))
+        .await;
+        match result {
+            Err(ModelError::Server { status, message }) => {
+                assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+                assert_eq!(message, r#"model "llama3" not found, try pulling it first"#);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        // Errors found once the stream has started are reported within it
+        let (result, tokens) = stream(Response::ok([
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"done\":false}\n",
+            "{\"error\":\"an error was encountered while running the model\"}\n",
+        ]))
+        .await;
+        match result {
+            Err(ModelError::Server { message, .. }) => {
+                assert_eq!(message, "an error was encountered while running the model");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(tokens, ["Hi"]);
+    }
+
+    #[tokio::test]
+    async fn complete() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"model":"llama3","message":{"role":"assistant","content":"5"},"done":true}"#,
+        ])])
+        .await;
+
+        let backend = Ollama::new()
+            .with_base_url(url)
+            .option("num_ctx", 4096)
+            .option("temperature", 0.2)
+            .keep_alive("10m");
+
+        let mut call = ModelMessage::new(Role::Assistant, "");
+        call.tool_calls = vec![ToolCall {
+            id: String::from("call_0"),
+            kind: String::from("function"),
+            function: FunctionCall {
+                name: String::from("add"),
+                arguments: String::from(r#"{"a":2,"b":3}"#),
+            },
+        }];
+        let mut result = ModelMessage::new(Role::User, "5");
+        result.tool_call_id = Some(Str::Borrowed("call_0"));
+
+        let messages = [ModelMessage::new(Role::User, "2 + 3?"), call, result];
+        let tools = ToolRegistry::new().register(
+            "add",
+            "Adds two numbers",
+            serde_json::json!({ "type": "object" }),
+            |_: Value| async { Ok::<_, std::convert::Infallible>(5) },
+        );
+        let params = ChatParams {
+            temperature: Some(1.0),
+            max_tokens: Some(16),
+            ..Default::default()
+        };
+
+        let request = ChatRequest::new("llama3", &messages)
+            .tools(tools.tools())
+            .params(&params);
+        let reply = backend.complete(request).await.unwrap();
+        assert_eq!(reply.content, "5");
+
+        let request = &server.await.unwrap()[0];
+        let body = serde_json::from_str::<Value>(&request.body).unwrap();
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["keep_alive"], "10m");
+        assert_eq!(body["options"]["num_ctx"], 4096);
+        assert_eq!(body["options"]["temperature"], 0.2);
+        assert_eq!(body["options"]["num_predict"], 16);
+        assert_eq!(body["tools"][0]["function"]["name"], "add");
+        assert_eq!(
+            body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+            serde_json::json!({ "a": 2, "b": 3 })
+        );
+        assert_eq!(body["messages"][2]["role"], "tool");
+    }
+
+    #[tokio::test]
+    async fn models() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"models":[{"name":"llama3:8b","modified_at":"2024-05-01T10:00:00Z","size":4661224676,"digest":"365c0bd3c000","details":{"format":"gguf","family":"llama","parameter_size":"8.0B","quantization_level":"Q4_0"}}]}"#,
+        ])])
+        .await;
+
+        let models = Ollama::new().with_base_url(url).models().await.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "llama3:8b");
+        assert_eq!(models[0].size, 4661224676);
+        assert_eq!(models[0].details.parameter_size, "8.0B");
+        assert_eq!(server.await.unwrap()[0].line, "GET /api/tags HTTP/1.1");
+    }
+}
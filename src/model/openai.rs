@@ -0,0 +1,390 @@
+use super::{send, ChatModel, ChatRequest, ModelError, ModelMessage, TokenCallback};
+use crate::{
+    tool::{FunctionCall, Tool, ToolCall},
+    Str,
+};
+use futures::StreamExt;
+use libopenai::{chat::Role, Client};
+use serde::Deserialize;
+use serde_json::Value;
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// [`ChatModel`] backed by OpenAI's API, or any other one compatible with it
+#[derive(Debug, Clone)]
+pub struct OpenAi {
+    pub client: Client,
+    /// Base URL of the API
+    pub base_url: Str,
+}
+
+impl OpenAi {
+    pub fn new(client: Client) -> Self {
+        return Self {
+            client,
+            base_url: Str::Borrowed(OPENAI_BASE_URL),
+        };
+    }
+
+    /// Sets the base URL of the API
+    pub fn with_base_url(mut self, base_url: impl Into<Str>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+}
+
+impl ChatModel for OpenAi {
+    type Error = ModelError;
+
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/chat/completions", self.base_url))
+                .json(&chat_body(request, false)),
+        )
+        .await?;
+        return read_completion(response).await;
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: ChatRequest<'_>,
+        on_token: &TokenCallback,
+    ) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/chat/completions", self.base_url))
+                .json(&chat_body(request, true)),
+        )
+        .await?;
+        return read_stream(response, on_token).await;
+    }
+}
+
+/// Body of a chat completion request, in the format of OpenAI's API
+pub(super) fn chat_body(request: ChatRequest<'_>, stream: bool) -> Value {
+    let messages = request
+        .messages
+        .iter()
+        .map(|message| match message.tool_call_id.as_deref() {
+            Some(id) => serde_json::json!({
+                "role": "tool",
+                "tool_call_id": id,
+                "content": message.content,
+            }),
+            None if !message.tool_calls.is_empty() => serde_json::json!({
+                "role": "assistant",
+                "content": message.content,
+                "tool_calls": message.tool_calls,
+            }),
+            None => serde_json::json!({ "role": message.role, "content": message.content }),
+        })
+        .collect::<Vec<_>>();
+
+    let mut body = serde_json::json!({
+        "model": request.model,
+        "messages": messages,
+        "stream": stream,
+    });
+
+    if !request.tools.is_empty() {
+        body["tools"] = request.tools.iter().map(Tool::definition).collect();
+    }
+
+    let params = request.params;
+    if let Some(temperature) = params.temperature {
+        body["temperature"] = Value::from(temperature);
+    }
+    if let Some(top_p) = params.top_p {
+        body["top_p"] = Value::from(top_p);
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        body["max_tokens"] = Value::from(max_tokens);
+    }
+    if !params.stop.is_empty() {
+        body["stop"] = params
+            .stop
+            .iter()
+            .map(|stop| Value::from(&**stop))
+            .collect();
+    }
+    if let Some(seed) = params.seed {
+        body["seed"] = Value::from(seed);
+    }
+
+    return body;
+}
+
+/// Reads the reply of a chat completion, in the format of OpenAI's API
+pub(super) async fn read_completion(
+    response: reqwest::Response,
+) -> Result<ModelMessage, ModelError> {
+    let mut completion = response.json::<Completion>().await?;
+    if completion.choices.is_empty() {
+        return Err(ModelError::NoChoices);
+    }
+
+    let message = completion.choices.swap_remove(0).message;
+    return Ok(ModelMessage {
+        role: Role::Assistant,
+        content: Str::Owned(message.content.unwrap_or_default()),
+        tool_calls: message.tool_calls.unwrap_or_default(),
+        tool_call_id: None,
+    });
+}
+
+/// Consumes the server-sent events of a streamed chat completion, building up the reply from their deltas
+pub(super) async fn read_stream(
+    response: reqwest::Response,
+    on_token: &TokenCallback,
+) -> Result<ModelMessage, ModelError> {
+    let mut stream = std::pin::pin!(response.bytes_stream());
+    let mut buffer = Vec::new();
+    let mut content = String::new();
+    let mut tool_calls = Vec::<ToolCall>::new();
+
+    'stream: while let Some(bytes) = stream.next().await {
+        buffer.extend_from_slice(&bytes?);
+
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line = buffer.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+
+            let data = data.trim();
+            if data == "[DONE]" {
+                break 'stream;
+            }
+
+            let chunk = serde_json::from_str::<ChunkCompletion>(data)?;
+            let Some(choice) = chunk.choices.into_iter().next() else {
+                continue;
+            };
+
+            if let Some(token) = choice.delta.content.filter(|token| !token.is_empty()) {
+                on_token.call(&token);
+                content.push_str(&token);
+            }
+
+            // Tool calls are streamed in fragments, identified by their index
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                while tool_calls.len() <= delta.index {
+                    tool_calls.push(ToolCall {
+                        id: String::new(),
+                        kind: String::from("function"),
+                        function: FunctionCall {
+                            name: String::new(),
+                            arguments: String::new(),
+                        },
+                    });
+                }
+
+                let call = &mut tool_calls[delta.index];
+                if let Some(id) = delta.id {
+                    call.id = id;
+                }
+                if let Some(function) = delta.function {
+                    call.function
+                        .name
+                        .push_str(&function.name.unwrap_or_default());
+                    call.function
+                        .arguments
+                        .push_str(&function.arguments.unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    return Ok(ModelMessage {
+        role: Role::Assistant,
+        content: Str::Owned(content),
+        tool_calls,
+        tool_call_id: None,
+    });
+}
+
+#[derive(Debug, Deserialize)]
+struct Completion {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: CompletionMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkCompletion {
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    delta: ChunkDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallDelta {
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<FunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::mock::{serve, Response};
+    use std::sync::{Arc, Mutex};
+
+    async fn stream(responses: Vec<Response>) -> (Result<ModelMessage, ModelError>, Vec<String>) {
+        let (url, server) = serve(responses).await;
+        let messages = [ModelMessage::new(Role::User, "Hi")];
+        let request = ChatRequest::new("gpt-4", &messages);
+
+        let tokens = Arc::new(Mutex::new(Vec::new()));
+        let on_token = TokenCallback::new({
+            let tokens = tokens.clone();
+            move |token| tokens.lock().unwrap().push(token.to_string())
+        });
+
+        let client = reqwest::Client::new();
+        let result = match send(client.post(url).json(&chat_body(request, true))).await {
+            Ok(response) => read_stream(response, &on_token).await,
+            Err(e) => Err(e),
+        };
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].line, "POST / HTTP/1.1");
+        assert!(requests[0].body.contains(r#""stream":true"#));
+
+        let tokens = tokens.lock().unwrap().clone();
+        return (result, tokens);
+    }
+
+    #[tokio::test]
+    async fn stream_split_across_chunks() {
+        let (reply, tokens) = stream(vec![Response::ok([
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"cont",
+            "ent\":\"Hel\"}}]}\r\n\r\ndata: {\"choices\":[{\"delta\":",
+            "{\"content\":\"lo\"}}]}\r\n",
+            "\r\n: keep-alive\n\ndata:{\"choices\":[{\"delta\":{\"content\":\"!\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        ])])
+        .await;
+
+        let reply = reply.unwrap();
+        assert_eq!(reply.content, "Hello!");
+        assert!(reply.tool_calls.is_empty());
+        assert_eq!(tokens, ["Hel", "lo", "!"]);
+    }
+
+    #[tokio::test]
+    async fn stream_ends_at_done() {
+        let (reply, tokens) = stream(vec![Response::ok([
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\ndata: [DONE]\n\n",
+            "data: {not json\n\n",
+        ])])
+        .await;
+
+        assert_eq!(reply.unwrap().content, "Hi");
+        assert_eq!(tokens, ["Hi"]);
+    }
+
+    #[tokio::test]
+    async fn stream_tool_call_fragments() {
+        let (reply, tokens) = stream(vec![Response::ok([
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_a\",\"type\":\"function\",\"function\":{\"name\":\"add\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"a\\\":\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_b\",\"type\":\"function\",\"function\":{\"name\":\"neg\",\"arguments\":\"{\\\"a\\\":1}\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"2}\"}}]}}]}\n\n",
+            "data: [DONE]\n\n",
+        ])])
+        .await;
+
+        let reply = reply.unwrap();
+        assert!(tokens.is_empty());
+        assert_eq!(reply.tool_calls.len(), 2);
+        assert_eq!(reply.tool_calls[0].id, "call_a");
+        assert_eq!(reply.tool_calls[0].function.name, "add");
+        assert_eq!(reply.tool_calls[0].function.arguments, r#"{"a":2}"#);
+        assert_eq!(reply.tool_calls[1].id, "call_b");
+        assert_eq!(reply.tool_calls[1].function.name, "neg");
+        assert_eq!(reply.tool_calls[1].function.arguments, r#"{"a":1}"#);
+    }
+
+    #[tokio::test]
+    async fn server_errors() {
+        let (result, _) = stream(vec![Response::status(
+            401,
+            r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#,
+        )])
+        .await;
+        match result {
+            Err(ModelError::Server { status, message }) => {
+                assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+                assert_eq!(message, "Incorrect API key provided");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let (result, _) = stream(vec![Response::status(503, "Service Unavailable")]).await;
+        match result {
+            Err(ModelError::Server { status, message }) => {
+                assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(message, "Service Unavailable");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn completion() {
+        let (url, server) = serve(vec![
+            Response::ok([r#"{"choices":[{"message":{"role":"assistant","content":null,"tool_calls":[{"id":"call_a","type":"function","function":{"name":"add","arguments":"{}"}}]}}]}"#]),
+            Response::ok([r#"{"choices":[]}"#]),
+        ])
+        .await;
+
+        let client = reqwest::Client::new();
+        let response = send(client.post(&url)).await.unwrap();
+        let reply = read_completion(response).await.unwrap();
+        assert_eq!(reply.content, "");
+        assert_eq!(reply.tool_calls[0].function.name, "add");
+
+        let response = send(client.post(&url)).await.unwrap();
+        assert!(matches!(
+            read_completion(response).await,
+            Err(ModelError::NoChoices)
+        ));
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use super::{
+    openai::{chat_body, read_completion, read_stream},
+    send, ChatModel, ChatRequest, ModelError, ModelMessage, TokenCallback,
+};
+use crate::Str;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Default address of llama.cpp's server (`llama-server`)
+const LLAMA_CPP_BASE_URL: &str = "http://localhost:8080/v1";
+
+/// [`ChatModel`] backed by a local server exposing an OpenAI-compatible API, like llama.cpp's `llama-server`
+/// (which it points to by default), vLLM or LM Studio.
+///
+/// Unlike [`OpenAi`](super::openai::OpenAi), it doesn't need an API key, and it can send the options specific
+/// to the server alongside the standard ones. It only talks to the OpenAI-compatible endpoints
+/// (`/chat/completions` and `/models`): llama.cpp's native ones are used by [`LlamaCpp`](super::llama_cpp::LlamaCpp).
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatible {
+    pub client: reqwest::Client,
+    /// Base URL of the API, including it's version prefix (like `http://localhost:8080/v1`)
+    pub base_url: Str,
+    /// Options specific to the server (like llama.cpp's `top_k`, `min_p` or `grammar`), sent alongside the
+    /// standard ones, and taking precedence over [`ChatParams`](super::ChatParams)
+    pub options: Map<String, Value>,
+}
+
+impl OpenAiCompatible {
+    pub fn new() -> Self {
+        return Self {
+            client: reqwest::Client::new(),
+            base_url: Str::Borrowed(LLAMA_CPP_BASE_URL),
+            options: Map::new(),
+        };
+    }
+
+    /// Sets the base URL of the API, including it's version prefix (like `http://localhost:8000/v1`)
+    pub fn with_base_url(mut self, base_url: impl Into<Str>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets an option specific to the server (like llama.cpp's `top_k`, `min_p` or `grammar`)
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Lists the identifiers of the models served by the server
+    pub async fn models(&self) -> Result<Vec<String>, ModelError> {
+        #[derive(Deserialize)]
+        struct Models {
+            data: Vec<Model>,
+        }
+
+        #[derive(Deserialize)]
+        struct Model {
+            id: String,
+        }
+
+        let response = send(self.client.get(format!("{}/models", self.base_url))).await?;
+        let models = response.json::<Models>().await?;
+        return Ok(models.data.into_iter().map(|model| model.id).collect());
+    }
+
+    /// Body of a chat request, with the server's own options alongside the standard ones
+    fn body(&self, request: ChatRequest<'_>, stream: bool) -> Value {
+        let mut body = chat_body(request, stream);
+        if let Value::Object(body) = &mut body {
+            body.extend(self.options.clone());
+        }
+        return body;
+    }
+}
+
+impl Default for OpenAiCompatible {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatModel for OpenAiCompatible {
+    type Error = ModelError;
+
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/chat/completions", self.base_url))
+                .json(&self.body(request, false)),
+        )
+        .await?;
+        return read_completion(response).await;
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: ChatRequest<'_>,
+        on_token: &TokenCallback,
+    ) -> Result<ModelMessage, Self::Error> {
+        let response = send(
+            self.client
+                .post(format!("{}/chat/completions", self.base_url))
+                .json(&self.body(request, true)),
+        )
+        .await?;
+        return read_stream(response, on_token).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        mock::{serve, Response},
+        ChatParams,
+    };
+    use libopenai::chat::Role;
+
+    #[tokio::test]
+    async fn models() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"object":"list","data":[{"id":"qwen2.5-7b-instruct-q4_k_m.gguf","object":"model"}]}"#,
+        ])])
+        .await;
+
+        let backend = OpenAiCompatible::new().with_base_url(format!("{url}/v1"));
+        let models = backend.models().await.unwrap();
+        assert_eq!(models, ["qwen2.5-7b-instruct-q4_k_m.gguf"]);
+        assert_eq!(server.await.unwrap()[0].line, "GET /v1/models HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn options() {
+        let (url, server) = serve(vec![Response::ok([
+            r#"{"choices":[{"message":{"role":"assistant","content":"Hi!"}}]}"#,
+        ])])
+        .await;
+
+        let backend = OpenAiCompatible::new()
+            .with_base_url(format!("{url}/v1"))
+            .option("top_k", 40)
+            .option("temperature", 0.5);
+
+        let messages = [ModelMessage::new(Role::User, "Hi")];
+        let params = ChatParams {
+            temperature: Some(1.0),
+            max_tokens: Some(16),
+            ..Default::default()
+        };
+        let reply = backend
+            .complete(ChatRequest::new("qwen", &messages).params(&params))
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hi!");
+
+        let request = &server.await.unwrap()[0];
+        assert_eq!(request.line, "POST /v1/chat/completions HTTP/1.1");
+
+        let body = serde_json::from_str::<Value>(&request.body).unwrap();
+        assert_eq!(body["top_k"], 40);
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["max_tokens"], 16);
+    }
+}
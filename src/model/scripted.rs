@@ -0,0 +1,69 @@
+//! Chat model answering with canned replies, used to test the agents built on top of [`ChatModel`]
+
+use super::{ChatModel, ChatRequest, ModelMessage};
+use crate::tool::{FunctionCall, ToolCall};
+use libopenai::chat::Role;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+/// Error returned once every reply has been used
+#[derive(Debug, Error)]
+#[error("No scripted replies left")]
+pub(crate) struct ScriptEnded;
+
+/// Chat model that answers every request with the next of it's replies.
+///
+/// Clones share their replies and requests, so that a clone can be kept to inspect what the agent sent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scripted {
+    replies: Arc<Mutex<VecDeque<ModelMessage>>>,
+    requests: Arc<Mutex<Vec<Vec<ModelMessage>>>>,
+}
+
+impl Scripted {
+    pub fn new(replies: impl IntoIterator<Item = ModelMessage>) -> Self {
+        return Self {
+            replies: Arc::new(Mutex::new(replies.into_iter().collect())),
+            requests: Arc::default(),
+        };
+    }
+
+    /// Messages of every request received so far
+    pub fn requests(&self) -> Vec<Vec<ModelMessage>> {
+        return self.requests.lock().unwrap().clone();
+    }
+}
+
+impl ChatModel for Scripted {
+    type Error = ScriptEnded;
+
+    async fn complete(&self, request: ChatRequest<'_>) -> Result<ModelMessage, Self::Error> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(request.messages.to_vec());
+        return self.replies.lock().unwrap().pop_front().ok_or(ScriptEnded);
+    }
+}
+
+/// Reply with the specified content
+pub(crate) fn reply(content: &str) -> ModelMessage {
+    return ModelMessage::new(Role::Assistant, content.to_string());
+}
+
+/// Reply asking to call the specified tool
+pub(crate) fn tool_call(id: &str, name: &str, arguments: &str) -> ModelMessage {
+    let mut message = reply("");
+    message.tool_calls.push(ToolCall {
+        id: id.to_string(),
+        kind: "function".to_string(),
+        function: FunctionCall {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        },
+    });
+    return message;
+}
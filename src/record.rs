@@ -1,4 +1,4 @@
-use crate::Str;
+use crate::{tool::ToolCall, Str};
 use libopenai::chat::{Message, Role};
 use std::convert::Infallible;
 
@@ -12,18 +12,76 @@ pub trait Record: 'static {
     fn push_message(&mut self, message: Message<'static>) -> Result<(), Self::Error> {
         self.push(message.role, message.content)
     }
+
+    /// Pushes a message written by the agent with the specified name.
+    ///
+    /// Records that don't keep track of who wrote each message ignore the name.
+    #[inline]
+    fn push_named(
+        &mut self,
+        _name: Option<&str>,
+        role: Role,
+        content: impl Into<Str>,
+    ) -> Result<(), Self::Error> {
+        self.push(role, content)
+    }
+
+    /// Pushes a message written by the agent with the specified name.
+    ///
+    /// Records that don't keep track of who wrote each message ignore the name.
+    #[inline]
+    fn push_message_named(
+        &mut self,
+        name: Option<&str>,
+        message: Message<'static>,
+    ) -> Result<(), Self::Error> {
+        self.push_named(name, message.role, message.content)
+    }
+
+    /// Attributes the messages pushed without a name to the agent with the specified name (or to nobody),
+    /// returning the agent they were attributed to before.
+    ///
+    /// Records that don't keep track of who wrote each message ignore the name.
+    #[inline]
+    fn set_speaker(&mut self, _name: Option<Str>) -> Option<Str> {
+        return None;
+    }
+}
+
+/// A record whose state can be saved, and later restored
+pub trait Snapshot: Record {
+    type State;
+
+    fn snapshot(&self) -> Self::State;
+    fn restore(&mut self, state: Self::State);
+}
+
+/// Information about a message of a [`ChatRecord`], besides it's role and content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// Name of the agent that wrote the message
+    pub author: Option<Str>,
+    /// Tools the model asked to call alongside the message
+    pub tool_calls: Vec<ToolCall>,
+    /// Identifier of the tool call whose result is the message, if any
+    pub tool_call_id: Option<Str>,
 }
 
-/// A chat-based record
+/// A chat-based record, which keeps track of the name of the agent that wrote each message,
+/// and of the tool calls made through it
 #[derive(Debug, Clone)]
 pub struct ChatRecord {
     messages: Vec<Message<'static>>,
+    metadata: Vec<Metadata>,
+    speaker: Option<Str>,
 }
 
 impl ChatRecord {
     pub fn new() -> Self {
         return Self {
             messages: Vec::new(),
+            metadata: Vec::new(),
+            speaker: None,
         };
     }
 
@@ -31,6 +89,100 @@ impl ChatRecord {
     pub fn messages(&self) -> &[Message<'static>] {
         return &self.messages;
     }
+
+    /// Metadata of the `i`-th message
+    #[inline]
+    pub fn metadata(&self, i: usize) -> Option<&Metadata> {
+        return self.metadata.get(i);
+    }
+
+    /// Name of the agent that wrote the `i`-th message, if known
+    #[inline]
+    pub fn author(&self, i: usize) -> Option<&str> {
+        return self.metadata.get(i).and_then(|meta| meta.author.as_deref());
+    }
+
+    /// Name of the agent the messages pushed without a name are attributed to, if any (see [`Record::set_speaker`])
+    #[inline]
+    pub fn speaker(&self) -> Option<&str> {
+        return self.speaker.as_deref();
+    }
+
+    /// Iterates over the messages, alongside the name of the agent that wrote them (if known)
+    pub fn entries(&self) -> impl Iterator<Item = (Option<&str>, &Message<'static>)> {
+        return self
+            .metadata
+            .iter()
+            .map(|meta| meta.author.as_deref())
+            .zip(self.messages.iter());
+    }
+
+    /// Pushes a message alongside it's metadata.
+    ///
+    /// If the metadata has no author, the message is attributed to the current speaker (see [`Record::set_speaker`]).
+    pub fn push_with_metadata(&mut self, message: Message<'static>, mut metadata: Metadata) {
+        if metadata.author.is_none() {
+            metadata.author = self.speaker.clone();
+        }
+        self.messages.push(message);
+        self.metadata.push(metadata);
+    }
+
+    /// Pushes an [`Assistant`](Role::Assistant) message, alongside the tools the model asked to call
+    pub fn push_tool_calls(
+        &mut self,
+        name: Option<&str>,
+        content: impl Into<Str>,
+        tool_calls: Vec<ToolCall>,
+    ) {
+        self.push_with_metadata(
+            Message::new(Role::Assistant, content),
+            Metadata {
+                author: name.map(|name| Str::Owned(name.to_string())),
+                tool_calls,
+                tool_call_id: None,
+            },
+        );
+    }
+
+    /// Pushes the result of the tool call with the specified identifier.
+    ///
+    /// Tool results are stored as [`User`](Role::User) messages, and only sent as tool messages
+    /// to the model that asked for the call.
+    pub fn push_tool_result(
+        &mut self,
+        name: Option<&str>,
+        tool_call_id: impl Into<Str>,
+        content: impl Into<Str>,
+    ) {
+        self.push_with_metadata(
+            Message::new(Role::User, content),
+            Metadata {
+                author: name.map(|name| Str::Owned(name.to_string())),
+                tool_calls: Vec::new(),
+                tool_call_id: Some(tool_call_id.into()),
+            },
+        );
+    }
+
+    /// Transcript as seen by the agent with the specified name.
+    ///
+    /// The agent's own messages are shown as [`Assistant`](Role::Assistant) messages, whilst the ones
+    /// written by other named agents are shown as [`User`](Role::User) messages, prefixed by their author's name.
+    pub fn view_as(&self, name: &str) -> Vec<Message<'static>> {
+        return self
+            .entries()
+            .map(|(author, message)| match author {
+                Some(author) if author == name => {
+                    Message::new(Role::Assistant, message.content.clone())
+                }
+                Some(author) if !matches!(message.role, Role::System) => {
+                    Message::new(Role::User, format!("{author}: {}", message.content))
+                }
+                _ => message.clone(),
+            })
+            .collect();
+    }
 }
 
 impl Record for ChatRecord {
@@ -38,15 +190,60 @@ impl Record for ChatRecord {
 
     #[inline]
     fn push(&mut self, role: Role, content: impl Into<Str>) -> Result<(), Self::Error> {
-        self.messages.push(Message::new(role, content));
-        return Ok(());
+        return self.push_named(None, role, content);
     }
 
     #[inline]
     fn push_message(&mut self, message: Message<'static>) -> Result<(), Self::Error> {
-        self.messages.push(message);
+        return self.push_message_named(None, message);
+    }
+
+    #[inline]
+    fn push_named(
+        &mut self,
+        name: Option<&str>,
+        role: Role,
+        content: impl Into<Str>,
+    ) -> Result<(), Self::Error> {
+        return self.push_message_named(name, Message::new(role, content));
+    }
+
+    #[inline]
+    fn push_message_named(
+        &mut self,
+        name: Option<&str>,
+        message: Message<'static>,
+    ) -> Result<(), Self::Error> {
+        self.push_with_metadata(
+            message,
+            Metadata {
+                author: name.map(|name| Str::Owned(name.to_string())),
+                ..Default::default()
+            },
+        );
         return Ok(());
     }
+
+    #[inline]
+    fn set_speaker(&mut self, name: Option<Str>) -> Option<Str> {
+        return std::mem::replace(&mut self.speaker, name);
+    }
+}
+
+/// Agents may replace the whole record (like [`KeepFirst`](crate::control_flow::parallel::KeepFirst) does),
+/// so the snapshot is a copy of it
+impl Snapshot for ChatRecord {
+    type State = ChatRecord;
+
+    #[inline]
+    fn snapshot(&self) -> Self::State {
+        return self.clone();
+    }
+
+    #[inline]
+    fn restore(&mut self, state: Self::State) {
+        *self = state;
+    }
 }
 
 impl Default for ChatRecord {
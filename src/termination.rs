@@ -0,0 +1,196 @@
+use crate::{control_flow::r#while::LoopPredicate, record::ChatRecord, Str};
+use std::{
+    ops::{BitAnd, BitOr, Not},
+    time::{Duration, Instant},
+};
+
+/// Condition that ends a conversation.
+///
+/// Conditions can be combined with the `&`, `|` and `!` operators.
+pub trait Termination<R> {
+    /// Checks whether the conversation should end, after `turns` turns have been played
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool;
+}
+
+/// Ends the conversation after the specified number of turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxTurns(pub usize);
+
+impl<R> Termination<R> for MaxTurns {
+    #[inline]
+    fn should_terminate(&mut self, _: &R, turns: usize) -> bool {
+        return turns >= self.0;
+    }
+}
+
+/// Ends the conversation once the last message contains the specified keyword (like `"TERMINATE"`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keyword(pub Str);
+
+impl Keyword {
+    pub fn new(keyword: impl Into<Str>) -> Self {
+        return Self(keyword.into());
+    }
+}
+
+impl Termination<ChatRecord> for Keyword {
+    fn should_terminate(&mut self, record: &ChatRecord, _: usize) -> bool {
+        return record
+            .messages()
+            .last()
+            .is_some_and(|message| message.content.contains(&*self.0));
+    }
+}
+
+/// Ends the conversation once the record exceeds the specified number of tokens.
+///
+/// Tokens are estimated as one every four characters, which is a good approximation for english text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBudget(pub usize);
+
+impl Termination<ChatRecord> for TokenBudget {
+    fn should_terminate(&mut self, record: &ChatRecord, _: usize) -> bool {
+        let chars = record
+            .messages()
+            .iter()
+            .map(|message| message.content.chars().count())
+            .sum::<usize>();
+        return chars.div_ceil(4) >= self.0;
+    }
+}
+
+/// Ends the conversation once the specified amount of time has passed since the condition was first checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLimit {
+    limit: Duration,
+    start: Option<Instant>,
+}
+
+impl TimeLimit {
+    pub fn new(limit: Duration) -> Self {
+        return Self { limit, start: None };
+    }
+}
+
+impl<R> Termination<R> for TimeLimit {
+    fn should_terminate(&mut self, _: &R, _: usize) -> bool {
+        return self.start.get_or_insert_with(Instant::now).elapsed() >= self.limit;
+    }
+}
+
+/// Ends the conversation once a custom predicate holds
+#[derive(Debug, Clone, Copy)]
+pub struct Custom<F>(pub F);
+
+/// Uses a custom predicate as termination condition.
+///
+/// Closures can be used directly as termination conditions, but wrapping them allows combining them with other conditions.
+#[inline]
+pub fn custom<R, F: FnMut(&R, usize) -> bool>(f: F) -> Custom<F> {
+    return Custom(f);
+}
+
+impl<R, F: FnMut(&R, usize) -> bool> Termination<R> for Custom<F> {
+    #[inline]
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool {
+        return (self.0)(record, turns);
+    }
+}
+
+impl<R, F: FnMut(&R, usize) -> bool> Termination<R> for F {
+    #[inline]
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool {
+        return (self)(record, turns);
+    }
+}
+
+/// Ends the conversation once both conditions hold
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<R, A: Termination<R>, B: Termination<R>> Termination<R> for And<A, B> {
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool {
+        // Both conditions are always checked, so that stateful ones (like `TimeLimit`) are kept up to date
+        let a = self.0.should_terminate(record, turns);
+        let b = self.1.should_terminate(record, turns);
+        return a && b;
+    }
+}
+
+/// Ends the conversation once either condition holds
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<R, A: Termination<R>, B: Termination<R>> Termination<R> for Or<A, B> {
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool {
+        // Both conditions are always checked, so that stateful ones (like `TimeLimit`) are kept up to date
+        let a = self.0.should_terminate(record, turns);
+        let b = self.1.should_terminate(record, turns);
+        return a || b;
+    }
+}
+
+/// Ends the conversation once the condition doesn't hold
+#[derive(Debug, Clone, Copy)]
+pub struct Inverse<T>(pub T);
+
+impl<R, T: Termination<R>> Termination<R> for Inverse<T> {
+    #[inline]
+    fn should_terminate(&mut self, record: &R, turns: usize) -> bool {
+        return !self.0.should_terminate(record, turns);
+    }
+}
+
+macro_rules! impl_ops {
+    ($($ty:ident $(<$($generic:ident),+>)?),+ $(,)?) => {
+        $(
+            impl<$($($generic,)+)? Rhs> BitAnd<Rhs> for $ty $(<$($generic),+>)? {
+                type Output = And<Self, Rhs>;
+
+                #[inline]
+                fn bitand(self, rhs: Rhs) -> Self::Output {
+                    return And(self, rhs);
+                }
+            }
+
+            impl<$($($generic,)+)? Rhs> BitOr<Rhs> for $ty $(<$($generic),+>)? {
+                type Output = Or<Self, Rhs>;
+
+                #[inline]
+                fn bitor(self, rhs: Rhs) -> Self::Output {
+                    return Or(self, rhs);
+                }
+            }
+
+            impl$(<$($generic),+>)? Not for $ty $(<$($generic),+>)? {
+                type Output = Inverse<Self>;
+
+                #[inline]
+                fn not(self) -> Self::Output {
+                    return Inverse(self);
+                }
+            }
+        )+
+    };
+}
+
+impl_ops!(
+    MaxTurns,
+    Keyword,
+    TokenBudget,
+    TimeLimit,
+    Custom<F>,
+    And<A, B>,
+    Or<A, B>,
+    Inverse<T>
+);
+
+/// Loop predicate that holds until the termination condition does
+pub struct Until<T>(pub(crate) T);
+
+impl<R, T: Termination<R>> LoopPredicate<R> for Until<T> {
+    #[inline]
+    async fn test(&mut self, record: &mut R, iteration: usize) -> bool {
+        return !self.0.should_terminate(record, iteration);
+    }
+}
@@ -0,0 +1,68 @@
+//! Record and agents used to test the conversation constructs
+
+use crate::{
+    agent::Agent,
+    record::{Record, Snapshot},
+    Str,
+};
+use libopenai::chat::Role;
+use std::{convert::Infallible, time::Duration};
+use thiserror::Error;
+
+/// Record that only keeps the content of every message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Log(pub Vec<String>);
+
+impl Record for Log {
+    type Error = Infallible;
+
+    fn push(&mut self, _: Role, content: impl Into<Str>) -> Result<(), Self::Error> {
+        self.0.push(content.into().into_owned());
+        return Ok(());
+    }
+}
+
+impl Snapshot for Log {
+    type State = Vec<String>;
+
+    fn snapshot(&self) -> Self::State {
+        return self.0.clone();
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.0 = state;
+    }
+}
+
+/// Error returned by the failing agents of the tests
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{0}")]
+pub(crate) struct Failure(pub &'static str);
+
+/// Agent that always fails with the specified message
+pub(crate) fn fail(message: &'static str) -> Result<(), Failure> {
+    return Err(Failure(message));
+}
+
+/// Agent that pushes the specified message
+pub(crate) fn say(content: &str) -> String {
+    return content.to_string();
+}
+
+/// Agent that pushes it's message after waiting for the specified number of milliseconds
+pub(crate) struct Delayed {
+    pub millis: u64,
+    pub content: &'static str,
+}
+
+impl<R: Record> Agent<R> for Delayed
+where
+    R::Error: 'static + std::error::Error + Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn handle(&mut self, record: &mut R) -> Result<(), Self::Error> {
+        tokio::time::sleep(Duration::from_millis(self.millis)).await;
+        return record.push(Role::User, self.content);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::Str;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+use thiserror::Error;
+
+pub use rustygen_macros::{tool, ToolSchema};
+
+type ToolFuture = Pin<Box<dyn Future<Output = color_eyre::Result<Value>>>>;
+type ToolHandler = Arc<dyn Send + Sync + Fn(&str) -> ToolFuture>;
+
+#[derive(Debug, Error)]
+pub enum ToolError {
+    #[error("Unknown tool `{0}`")]
+    UnknownTool(Str),
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(#[from] serde_json::Error),
+}
+
+/// Asynchronous Rust function that can be called by a model
+#[derive(Clone)]
+pub struct Tool {
+    pub name: Str,
+    pub description: Option<Str>,
+    /// JSON schema of the tool's parameters
+    pub parameters: Value,
+    handler: ToolHandler,
+}
+
+impl Tool {
+    /// Creates a new tool, whose arguments are deserialized from JSON before calling `f`
+    pub fn new<A, O, E, F, Fut>(
+        name: impl Into<Str>,
+        description: Option<Str>,
+        parameters: Value,
+        f: F,
+    ) -> Self
+    where
+        A: 'static + DeserializeOwned,
+        O: Serialize,
+        E: Into<color_eyre::Report>,
+        F: 'static + Send + Sync + Fn(A) -> Fut,
+        Fut: 'static + Future<Output = Result<O, E>>,
+    {
+        let f = Arc::new(f);
+        let handler = move |arguments: &str| -> ToolFuture {
+            let arguments = serde_json::from_str::<A>(arguments);
+            let f = f.clone();
+            return Box::pin(async move {
+                let output = f(arguments.map_err(ToolError::InvalidArguments)?)
+                    .await
+                    .map_err(Into::into)?;
+                return Ok(serde_json::to_value(output)?);
+            });
+        };
+
+        return Self {
+            name: name.into(),
+            description,
+            parameters,
+            handler: Arc::new(handler),
+        };
+    }
+
+    /// Calls the tool with it's JSON-encoded arguments
+    pub async fn call(&self, arguments: &str) -> color_eyre::Result<Value> {
+        return (self.handler)(arguments).await;
+    }
+
+    /// Definition of the tool, as expected by OpenAI's API
+    pub fn definition(&self) -> Value {
+        let mut function = serde_json::json!({
+            "name": self.name,
+            "parameters": self.parameters,
+        });
+
+        if let Some(description) = self.description.as_deref() {
+            function["description"] = Value::from(description);
+        }
+
+        return serde_json::json!({
+            "type": "function",
+            "function": function,
+        });
+    }
+}
+
+impl Debug for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tool")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("parameters", &self.parameters)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Set of tools available to a model
+#[derive(Debug, Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Tool>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        return Self { tools: Vec::new() };
+    }
+
+    /// Adds a tool to the registry
+    pub fn tool(mut self, tool: Tool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Adds a tool to the registry, whose arguments are deserialized from JSON before calling `f`
+    pub fn register<A, O, E, F, Fut>(
+        self,
+        name: impl Into<Str>,
+        description: impl Into<Str>,
+        parameters: Value,
+        f: F,
+    ) -> Self
+    where
+        A: 'static + DeserializeOwned,
+        O: Serialize,
+        E: Into<color_eyre::Report>,
+        F: 'static + Send + Sync + Fn(A) -> Fut,
+        Fut: 'static + Future<Output = Result<O, E>>,
+    {
+        return self.tool(Tool::new(name, Some(description.into()), parameters, f));
+    }
+
+    /// Adds a tool to the registry, whose parameters' schema is the one of `A`
+    pub fn register_typed<A, O, E, F, Fut>(
+        self,
+        name: impl Into<Str>,
+        description: impl Into<Str>,
+        f: F,
+    ) -> Self
+    where
+        A: 'static + ToolSchema + DeserializeOwned,
+        O: Serialize,
+        E: Into<color_eyre::Report>,
+        F: 'static + Send + Sync + Fn(A) -> Fut,
+        Fut: 'static + Future<Output = Result<O, E>>,
+    {
+        return self.register(name, description, A::schema(), f);
+    }
+
+    #[inline]
+    pub fn tools(&self) -> &[Tool] {
+        return &self.tools;
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.tools.is_empty();
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tool> {
+        return self.tools.iter().find(|tool| tool.name == name);
+    }
+
+    /// Calls the tool with the specified name, with it's JSON-encoded arguments
+    pub async fn call(&self, name: &str, arguments: &str) -> color_eyre::Result<Value> {
+        return match self.get(name) {
+            Some(tool) => tool.call(arguments).await,
+            None => Err(ToolError::UnknownTool(Str::Owned(name.to_string())).into()),
+        };
+    }
+
+    /// Definitions of every tool, as expected by OpenAI's API
+    pub fn definitions(&self) -> Vec<Value> {
+        return self.tools.iter().map(Tool::definition).collect();
+    }
+}
+
+/// Call to a tool, requested by a model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments of the call
+    pub arguments: String,
+}
+
+/// Type whose values can be described by a JSON schema.
+///
+/// It can be derived for structs and enums (see [`ToolSchema`](derive@ToolSchema)).
+pub trait ToolSchema {
+    /// Whether the value can be omitted, when it's the field of an object
+    const OPTIONAL: bool = false;
+
+    fn schema() -> Value;
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty => $schema:tt),+ $(,)?) => {
+        $(
+            impl ToolSchema for $ty {
+                #[inline]
+                fn schema() -> Value {
+                    return serde_json::json!($schema);
+                }
+            }
+        )+
+    };
+}
+
+impl_schema! {
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer" },
+    i16 => { "type": "integer" },
+    i32 => { "type": "integer" },
+    i64 => { "type": "integer" },
+    i128 => { "type": "integer" },
+    isize => { "type": "integer" },
+    u8 => { "type": "integer", "minimum": 0 },
+    u16 => { "type": "integer", "minimum": 0 },
+    u32 => { "type": "integer", "minimum": 0 },
+    u64 => { "type": "integer", "minimum": 0 },
+    u128 => { "type": "integer", "minimum": 0 },
+    usize => { "type": "integer", "minimum": 0 },
+    f32 => { "type": "number" },
+    f64 => { "type": "number" },
+    char => { "type": "string", "minLength": 1, "maxLength": 1 },
+    String => { "type": "string" },
+    Str => { "type": "string" },
+    Value => {},
+}
+
+impl<T: ToolSchema> ToolSchema for Option<T> {
+    const OPTIONAL: bool = true;
+
+    #[inline]
+    fn schema() -> Value {
+        return T::schema();
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Box<T> {
+    const OPTIONAL: bool = T::OPTIONAL;
+
+    #[inline]
+    fn schema() -> Value {
+        return T::schema();
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Vec<T> {
+    fn schema() -> Value {
+        return serde_json::json!({ "type": "array", "items": T::schema() });
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for HashMap<String, T> {
+    fn schema() -> Value {
+        return serde_json::json!({ "type": "object", "additionalProperties": T::schema() });
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        return serde_json::json!({ "type": "object", "additionalProperties": T::schema() });
+    }
+}
+
+/// Used by the code generated by [`tool`] and [`ToolSchema`](derive@ToolSchema)
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_json;
+    use serde_json::Value;
+
+    /// Adds a description to the schema, if any
+    pub fn describe(mut schema: Value, description: Option<&str>) -> Value {
+        if let (Some(description), Value::Object(schema)) = (description, &mut schema) {
+            schema.insert("description".into(), Value::from(description));
+        }
+        return schema;
+    }
+
+    /// Schema of an object, with it's properties given as `(name, schema, description, required)`
+    pub fn object(
+        description: Option<&str>,
+        properties: Vec<(&str, Value, Option<&str>, bool)>,
+    ) -> Value {
+        let required = properties
+            .iter()
+            .filter(|(_, _, _, required)| *required)
+            .map(|(name, ..)| Value::from(*name))
+            .collect::<Vec<_>>();
+
+        let properties = properties
+            .into_iter()
+            .map(|(name, schema, description, _)| (name.to_string(), describe(schema, description)))
+            .collect::<serde_json::Map<_, _>>();
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        return describe(schema, description);
+    }
+
+    /// Schema of a string, restricted to the specified variants
+    pub fn enumeration(description: Option<&str>, variants: &[&str]) -> Value {
+        let schema = serde_json::json!({ "type": "string", "enum": variants });
+        return describe(schema, description);
+    }
+}
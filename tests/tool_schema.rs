@@ -0,0 +1,142 @@
+use rustygen::tool::{tool, ToolSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Location to look up
+#[derive(Deserialize, ToolSchema)]
+#[allow(dead_code)]
+struct Location {
+    /// Name of the city
+    city: String,
+    #[serde(rename = "country_code")]
+    country: Option<String>,
+    #[serde(default)]
+    population: u32,
+}
+
+/// Unit of the temperature
+#[derive(Deserialize, ToolSchema)]
+#[allow(dead_code)]
+enum Unit {
+    Celsius,
+    #[serde(rename = "F")]
+    Fahrenheit,
+}
+
+/// Identifier of a user
+#[derive(Deserialize, ToolSchema)]
+#[allow(dead_code)]
+struct UserId(u64);
+
+#[derive(Debug, PartialEq, Serialize)]
+struct SearchResult {
+    hits: Vec<String>,
+}
+
+/// Adds two numbers
+///
+/// # Arguments
+/// * `a` - First number
+/// * `b` - Second number
+#[tool]
+async fn add(a: i64, b: i64) -> i64 {
+    return a + b;
+}
+
+/// Divides two numbers
+#[tool(name = "divide")]
+async fn div(a: f64, b: f64) -> color_eyre::Result<f64> {
+    if b == 0.0 {
+        return Err(color_eyre::eyre::eyre!("Division by zero"));
+    }
+    return Ok(a / b);
+}
+
+/// Searches the web
+#[tool]
+async fn search(query: String, limit: Option<usize>) -> SearchResult {
+    let hits = vec![query; limit.unwrap_or(1)];
+    return SearchResult { hits };
+}
+
+#[test]
+fn struct_schema() {
+    assert_eq!(
+        Location::schema(),
+        json!({
+            "type": "object",
+            "description": "Location to look up",
+            "properties": {
+                "city": { "type": "string", "description": "Name of the city" },
+                "country_code": { "type": "string" },
+                "population": { "type": "integer", "minimum": 0 },
+            },
+            "required": ["city"],
+        })
+    );
+}
+
+#[test]
+fn enum_schema() {
+    assert_eq!(
+        Unit::schema(),
+        json!({
+            "type": "string",
+            "description": "Unit of the temperature",
+            "enum": ["Celsius", "F"],
+        })
+    );
+}
+
+#[test]
+fn newtype_schema() {
+    assert_eq!(
+        UserId::schema(),
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "description": "Identifier of a user",
+        })
+    );
+}
+
+#[test]
+fn tool_schema() {
+    let tool = add_tool();
+    assert_eq!(tool.name, "add");
+    assert_eq!(tool.description.as_deref(), Some("Adds two numbers"));
+    assert_eq!(
+        tool.parameters,
+        json!({
+            "type": "object",
+            "properties": {
+                "a": { "type": "integer", "description": "First number" },
+                "b": { "type": "integer", "description": "Second number" },
+            },
+            "required": ["a", "b"],
+        })
+    );
+
+    let tool = search_tool();
+    assert_eq!(tool.parameters["required"], json!(["query"]));
+}
+
+#[tokio::test]
+async fn tool_call() {
+    assert_eq!(add_tool().call(r#"{"a":2,"b":3}"#).await.unwrap(), json!(5));
+    assert!(add_tool().call(r#"{"a":2}"#).await.is_err());
+
+    let tool = div_tool();
+    assert_eq!(tool.name, "divide");
+    assert_eq!(tool.call(r#"{"a":1,"b":2}"#).await.unwrap(), json!(0.5));
+    assert!(tool.call(r#"{"a":1,"b":0}"#).await.is_err());
+
+    // Return types that merely end in `Result` are returned as they are
+    assert_eq!(
+        search_tool()
+            .call(r#"{"query":"rust","limit":2}"#)
+            .await
+            .unwrap(),
+        json!({ "hits": ["rust", "rust"] })
+    );
+}
@@ -0,0 +1,8 @@
+use rustygen::tool::tool;
+
+#[tool]
+async fn greet(name: &str) -> String {
+    return format!("Hello, {name}!");
+}
+
+fn main() {}
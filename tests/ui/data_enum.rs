@@ -0,0 +1,9 @@
+use rustygen::tool::ToolSchema;
+
+#[derive(ToolSchema)]
+enum Shape {
+    Circle(f64),
+    Square { side: f64 },
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use rustygen::tool::tool;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Query {
+    text: String,
+}
+
+#[tool]
+async fn search(query: Query) -> String {
+    return query.text;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use rustygen::tool::tool;
+
+#[tool]
+fn add(a: i64, b: i64) -> i64 {
+    return a + b;
+}
+
+fn main() {}
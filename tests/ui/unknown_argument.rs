@@ -0,0 +1,13 @@
+use rustygen::tool::tool;
+
+/// Adds two numbers
+///
+/// # Arguments
+/// * `a` - First number
+/// * `c` - Second number
+#[tool]
+async fn add(a: i64, b: i64) -> i64 {
+    return a + b;
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use rustygen::tool::ToolSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, ToolSchema)]
+struct Query {
+    #[serde(flatten)]
+    options: std::collections::HashMap<String, String>,
+}
+
+fn main() {}